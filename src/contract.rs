@@ -1,26 +1,38 @@
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Api, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    Order, QuerierWrapper, Response, StdResult, Storage, Uint128, WasmMsg,
+    to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal256, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage,
+    Uint128, Uint256, Uint64, WasmMsg,
 };
 
 use cw2::set_contract_version;
 
-use cw3::{Proposal, Status, Vote, VoterDetail, VoterListResponse, VoterResponse, Votes};
+use cw3::{Ballot, Proposal, Status, Vote, VoterDetail, VoterListResponse, VoterResponse, Votes};
 
-use cw4::{Cw4Contract, MemberChangedHookMsg, MemberDiff, MEMBERS_KEY};
+use cw4::{Cw4Contract, Member, MemberChangedHookMsg, MemberDiff, MEMBERS_KEY};
 use cw_storage_plus::{Bound, Map};
-use cw_utils::{maybe_addr, Duration, Expiration, Threshold, ThresholdResponse};
+use cw_utils::{maybe_addr, must_pay, Duration, Expiration, ThresholdResponse};
 
 use crate::error::ContractError;
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, MigrateMsg, ProposalListResponse, ProposalResponse, QueryMsg,
-    VoteData, VoteInfo, VoteListResponse, VoteResponse,
+    ConfigUpdate, ExecuteMsg, GovernanceProposalListResponse, GovernanceProposalResponse, HookMsg,
+    InstantiateMsg, MigrateMsg, PendingConfigChangesResponse, PreviewAggregateResponse,
+    PriceResponse, ProposalFilter, ProposalListResponse, ProposalResponse, QueryMsg, RoleInfo,
+    RolesResponse, RoundDueResponse, RoundResponse, VoteData, VoteInfo, VoteListResponse,
+    VoteResponse, VoterBallot, VoterBallotsResponse,
 };
-use crate::state::{last_id, next_id, Config, Data, BALLOTS, CONFIG, PROPOSALS};
+use crate::state::{
+    ballots, has_role, last_id, next_id, proposals, status_key, Config, ConfigChange, Data,
+    GovProposal, MembersChange, PendingConfigChange, PendingOwner, PriceInfo, PriceKeyInfo, Role,
+    CONFIG, CONFIG_CHANGE_COUNT, GOV_BALLOTS, GOV_PROPOSALS, GOV_PROPOSAL_COUNT, MEMBERS,
+    MEMBER_SYNCS, NATIVE_MEMBERS, NATIVE_TOTAL_WEIGHT, PENDING_CONFIG_CHANGES, PENDING_OWNER,
+    PRICES, ROLES, ROUND_PRICES, UPDATE_REQUEST,
+};
+use crate::value::{Aggregate, Dispersion, FeedValue, Int128, ValueType};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-oracle-hub";
@@ -29,18 +41,23 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let group_addr = Cw4Contract(deps.api.addr_validate(&msg.group_addr).map_err(|_| {
-        ContractError::InvalidGroup {
-            addr: msg.group_addr.clone(),
+    let group_addr = match (msg.group_addr, msg.members) {
+        (Some(group_addr), None) => {
+            Some(Cw4Contract(deps.api.addr_validate(&group_addr).map_err(
+                |_| ContractError::InvalidGroup { addr: group_addr },
+            )?))
         }
-    })?);
-    let total_weight = group_addr.total_weight(&deps.querier)?;
-    msg.threshold.validate(total_weight)?;
-
+        (None, Some(members)) => {
+            let members = validate_members(deps.api, members, vec![])?;
+            update_native_members(deps.storage, env.block.height, members)?;
+            None
+        }
+        _ => return Err(ContractError::InvalidMembership {}),
+    };
     let proposal_deposit = msg
         .proposal_deposit
         .map(|deposit| deposit.into_checked(deps.as_ref()))
@@ -54,9 +71,19 @@ pub fn instantiate(
         max_submitting_period: msg.max_submitting_period,
         group_addr,
         proposal_deposit,
-        hook_contracts: msg.hook_contracts,
+        hook_contracts: validate_hooks(deps.api, msg.hook_contracts)?,
         price_keys: msg.price_keys,
+        feeds: BTreeMap::new(),
+        request_fee: None,
+        derived_keys: BTreeMap::new(),
+        price_key_infos: BTreeMap::new(),
+        min_voters: 0,
+        paused: false,
+        frozen_keys: BTreeSet::new(),
+        timelock: 0,
+        governance: None,
     };
+    validate_config(deps.storage, &deps.querier, &cfg)?;
     CONFIG.save(deps.storage, &cfg)?;
 
     Ok(Response::default())
@@ -73,60 +100,782 @@ pub fn execute(
         ExecuteMsg::Propose { data, latest } => execute_propose(deps, env, info, data, latest),
         ExecuteMsg::Vote { proposal_id, data } => execute_vote(deps, env, info, proposal_id, data),
         ExecuteMsg::Close { proposal_id } => execute_close(deps, env, info, proposal_id),
+        ExecuteMsg::Finalize { proposal_id } => execute_finalize(deps, env, info, proposal_id),
         ExecuteMsg::MemberChangedHook(MemberChangedHookMsg { diffs }) => {
             execute_membership_hook(deps, env, info, diffs)
         }
-        ExecuteMsg::UpdateConfig {
-            owner,
-            threshold,
-            max_submitting_period,
-            price_keys,
-            hook_contracts,
-        } => execute_update_config(
-            deps,
-            info,
-            owner,
-            threshold,
-            max_submitting_period,
-            price_keys,
-            hook_contracts,
-        ),
+        ExecuteMsg::SyncMembers { limit } => execute_sync_members(deps, env, limit),
+        ExecuteMsg::UpdateConfig(update) => execute_update_config(deps, env, info, update),
+        ExecuteMsg::UpdateGroup { group_addr } => execute_update_group(deps, env, info, group_addr),
+        ExecuteMsg::UpdateMembers { remove, add } => {
+            execute_update_members(deps, env, info, remove, add)
+        }
+        ExecuteMsg::ProposeConfigUpdate { update } => {
+            execute_propose_config_update(deps, env, info, update)
+        }
+        ExecuteMsg::VoteConfigUpdate { proposal_id, vote } => {
+            execute_vote_config_update(deps, env, info, proposal_id, vote)
+        }
+        ExecuteMsg::ExecuteConfigUpdate { proposal_id } => {
+            execute_execute_config_update(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::ApplyConfigChange { id } => execute_apply_config_change(deps, env, info, id),
+        ExecuteMsg::CancelConfigChange { id } => execute_cancel_config_change(deps, info, id),
+        ExecuteMsg::RequestUpdate { keys } => execute_request_update(deps, info, keys),
+        ExecuteMsg::ProposeOwner { owner, expires } => {
+            execute_propose_owner(deps, env, info, owner, expires)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipTransfer {} => execute_cancel_ownership_transfer(deps, info),
+        ExecuteMsg::Pause {} => execute_set_paused(deps, info, true),
+        ExecuteMsg::Unpause {} => execute_set_paused(deps, info, false),
+        ExecuteMsg::FreezeKey { key } => execute_freeze_key(deps, info, key, true),
+        ExecuteMsg::UnfreezeKey { key } => execute_freeze_key(deps, info, key, false),
+        ExecuteMsg::GrantRole { address, role } => execute_grant_role(deps, info, address, role),
+        ExecuteMsg::RevokeRole { address, role } => execute_revoke_role(deps, info, address, role),
     }
 }
 
 fn execute_update_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner: Option<String>,
-    threshold: Option<Threshold>,
-    max_submitting_period: Option<Duration>,
-    price_keys: Option<Vec<String>>,
-    hook_contracts: Option<Vec<Addr>>,
+    update: ConfigUpdate,
 ) -> Result<Response<Empty>, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-
-    if config.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    let config = CONFIG.load(deps.storage)?;
+
+    // each part of the config needs its own role
+    let mut required = vec![];
+    if update.price_keys.is_some()
+        || update.feeds.is_some()
+        || update.derived_keys.is_some()
+        || update.price_key_infos.is_some()
+    {
+        required.push(Role::KeyManager);
     }
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_validate(&owner)?;
+    if update.hook_contracts.is_some() {
+        required.push(Role::HookManager);
     }
-    if let Some(threshold) = threshold {
-        config.threshold = threshold;
+    if required.is_empty()
+        || update.threshold.is_some()
+        || update.max_submitting_period.is_some()
+        || update.request_fee.is_some()
+        || update.min_voters.is_some()
+        || update.timelock.is_some()
+        || update.governance.is_some()
+    {
+        required.push(Role::Admin);
+    }
+    for role in required {
+        if !has_role(deps.storage, &config, &info.sender, role)? {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+
+    update_config(deps, &env, update)
+}
+
+/// Applies the update right away, or queues its sensitive part while a timelock is set
+fn update_config(
+    deps: DepsMut,
+    env: &Env,
+    update: ConfigUpdate,
+) -> Result<Response<Empty>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let ConfigUpdate {
+        threshold,
+        max_submitting_period,
+        price_keys,
+        hook_contracts,
+        feeds,
+        request_fee,
+        derived_keys,
+        price_key_infos,
+        min_voters,
+        timelock,
+        governance,
+    } = update;
+
+    // changes to what consumers trust wait for the timelock, adding keys or raising it does not.
+    // Removals are queued as such, so that they do not undo what is changed meanwhile
+    let mut remove_price_keys = vec![];
+    let price_keys = price_keys.map(|price_keys| {
+        remove_price_keys = config
+            .price_keys
+            .iter()
+            .filter(|key| !price_keys.contains(key))
+            .cloned()
+            .collect();
+        if remove_price_keys.is_empty() {
+            return price_keys;
+        }
+        let added = price_keys
+            .into_iter()
+            .filter(|key| !config.price_keys.contains(key));
+        config.price_keys.iter().cloned().chain(added).collect()
+    });
+    let (mut add_hooks, mut remove_hooks) = (vec![], vec![]);
+    if let Some(hooks) = hook_contracts {
+        let hooks = validate_hooks(deps.api, hooks)?;
+        remove_hooks = config
+            .hook_contracts
+            .iter()
+            .filter(|hook| !hooks.contains(hook))
+            .cloned()
+            .collect();
+        add_hooks = hooks
+            .into_iter()
+            .filter(|hook| !config.hook_contracts.contains(hook))
+            .collect();
     }
+    let lowers_timelock = timelock.is_some_and(|timelock| timelock < config.timelock);
+    let (timelock, delayed_timelock) = if lowers_timelock {
+        (None, timelock)
+    } else {
+        (timelock, None)
+    };
+    let change = ConfigChange {
+        threshold,
+        remove_price_keys,
+        add_hooks,
+        remove_hooks,
+        timelock: delayed_timelock,
+        governance,
+        ..ConfigChange::default()
+    };
+
     if let Some(max_submitting_period) = max_submitting_period {
         config.max_submitting_period = max_submitting_period;
     }
     if let Some(price_keys) = price_keys {
-        config.price_keys = price_keys;
+        config.set_price_keys(price_keys);
+    }
+    if let Some(feeds) = feeds {
+        config.feeds = feeds;
+    }
+    if let Some(request_fee) = request_fee {
+        config.request_fee = request_fee;
+    }
+    if let Some(derived_keys) = derived_keys {
+        config.derived_keys = derived_keys;
+    }
+    if let Some(price_key_infos) = price_key_infos {
+        config.price_key_infos = price_key_infos;
+    }
+    if let Some(min_voters) = min_voters {
+        config.min_voters = min_voters;
+    }
+
+    let response = apply_or_queue_change(deps.storage, &deps.querier, env, &mut config, change)?
+        .add_attribute("action", "update_config");
+    if let Some(timelock) = timelock {
+        config.timelock = timelock;
+    }
+
+    validate_config(deps.storage, &deps.querier, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response)
+}
+
+pub fn execute_apply_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response<Empty>, ContractError> {
+    // anyone can apply a change once its timelock has passed
+    let pending = PENDING_CONFIG_CHANGES.load(deps.storage, id)?;
+    if env.block.time < pending.eta {
+        return Err(ContractError::TimelockNotExpired { eta: pending.eta });
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let response = apply_change(deps.storage, &env.block, &mut config, pending.change)?;
+    validate_config(deps.storage, &deps.querier, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_CONFIG_CHANGES.remove(deps.storage, id);
+
+    Ok(response
+        .add_attribute("action", "apply_config_change")
+        .add_attribute("sender", info.sender)
+        .add_attribute("config_change_id", id.to_string()))
+}
+
+/// Applies the change right away when there is no timelock, otherwise queues it
+fn apply_or_queue_change(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    config: &mut Config,
+    change: ConfigChange,
+) -> Result<Response<Empty>, ContractError> {
+    if config.timelock == 0 || change.is_empty() {
+        return apply_change(storage, &env.block, config, change);
+    }
+
+    // fail now rather than once the timelock has passed
+    let mut changed = config.clone();
+    change.clone().apply(&mut changed);
+    match &change.members {
+        Some(members) => {
+            let (total_weight, voters) = native_members_after(storage, members)?;
+            check_config(&changed, total_weight, voters >= changed.min_voters)?;
+        }
+        None => validate_config(storage, querier, &changed)?,
+    }
+
+    let id = CONFIG_CHANGE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    CONFIG_CHANGE_COUNT.save(storage, &id)?;
+    let eta = env.block.time.plus_seconds(config.timelock);
+    PENDING_CONFIG_CHANGES.save(storage, id, &PendingConfigChange { id, change, eta })?;
+    Ok(Response::new()
+        .add_attribute("config_change_id", id.to_string())
+        .add_attribute("eta", eta.to_string()))
+}
+
+/// Switching the group rejects what is still being voted on, as the weights of those
+/// proposals come from the old group. Member changes void the ballots of members that lost weight.
+fn apply_change(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    config: &mut Config,
+    mut change: ConfigChange,
+) -> Result<Response<Empty>, ContractError> {
+    let mut response = Response::new();
+    if let Some(members) = change.members.take() {
+        // a group may have been set since the change was queued
+        if config.group_addr.is_some() {
+            return Err(ContractError::NotNativeMembership {});
+        }
+        let diffs = update_native_members(storage, block.height, members)?;
+        let voided = void_ballots(storage, block, &diffs)?;
+        // attributes can not be empty
+        if !voided.is_empty() {
+            response = response.add_attribute("voided_ballots", voided.join(","));
+        }
+    }
+    if let Some(group_addr) = &change.group_addr {
+        if Some(group_addr) != config.group_addr.as_ref() {
+            response = response.add_messages(reject_open_proposals(storage, block)?);
+        }
+    }
+    change.apply(config);
+    Ok(response)
+}
+
+fn reject_open_proposals(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut msgs = vec![];
+
+    // only the last round can still be open, a passed one can still be finalized
+    let id = last_id(storage)?;
+    if let Some(mut prop) = proposals().may_load(storage, id)? {
+        if prop.status == Status::Open && prop.current_status(block) != Status::Passed {
+            prop.status = Status::Rejected;
+            proposals().save(storage, id, &prop)?;
+            if let Some(deposit) = prop.deposit {
+                if deposit.refund_failed_proposals {
+                    msgs.push(deposit.get_return_deposit_message(&prop.proposer)?);
+                }
+            }
+        }
+    }
+
+    let open = GOV_PROPOSALS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref().map_or(true, |(_, gov)| {
+                gov.proposal.current_status(block) == Status::Open
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, mut gov) in open {
+        gov.proposal.status = Status::Rejected;
+        GOV_PROPOSALS.save(storage, id, &gov)?;
+    }
+
+    Ok(msgs)
+}
+
+pub fn execute_update_group(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    group_addr: String,
+) -> Result<Response<Empty>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the new group must answer like a cw4 group before the threshold is checked against it
+    let invalid = || ContractError::InvalidGroup {
+        addr: group_addr.clone(),
+    };
+    let group = Cw4Contract(deps.api.addr_validate(&group_addr).map_err(|_| invalid())?);
+    group.total_weight(&deps.querier).map_err(|_| invalid())?;
+
+    let change = ConfigChange {
+        group_addr: Some(group),
+        ..ConfigChange::default()
+    };
+    let response = apply_or_queue_change(deps.storage, &deps.querier, &env, &mut config, change)?;
+    validate_config(deps.storage, &deps.querier, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response
+        .add_attribute("action", "update_group")
+        .add_attribute("group_addr", group_addr))
+}
+
+pub fn execute_cancel_config_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    PENDING_CONFIG_CHANGES.load(deps.storage, id)?;
+    PENDING_CONFIG_CHANGES.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_config_change")
+        .add_attribute("config_change_id", id.to_string()))
+}
+
+pub fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response<Empty>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Pauser)? {
+        return Err(ContractError::Unauthorized {});
     }
-    if let Some(hook_contracts) = hook_contracts {
-        config.hook_contracts = hook_contracts;
+    config.paused = paused;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+}
+
+pub fn execute_freeze_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    key: String,
+    frozen: bool,
+) -> Result<Response<Empty>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Pauser)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !config.price_keys.contains(&key) {
+        return Err(ContractError::UnknownPriceKey { key });
     }
 
+    // in-flight ballots stay valid, the key is only skipped when a round is aggregated
+    if frozen {
+        config.frozen_keys.insert(key.clone());
+    } else {
+        config.frozen_keys.remove(&key);
+    }
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    Ok(Response::new()
+        .add_attribute("action", if frozen { "freeze_key" } else { "unfreeze_key" })
+        .add_attribute("key", key))
+}
+
+pub fn execute_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.update(deps.storage, &address, |roles| -> StdResult<_> {
+        let mut roles = roles.unwrap_or_default();
+        if !roles.contains(&role) {
+            roles.push(role);
+            roles.sort();
+        }
+        Ok(roles)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("address", address)
+        .add_attribute("role", format!("{:?}", role)))
+}
+
+pub fn execute_revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &config, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    let mut roles = ROLES.may_load(deps.storage, &address)?.unwrap_or_default();
+    roles.retain(|r| *r != role);
+    if roles.is_empty() {
+        ROLES.remove(deps.storage, &address);
+    } else {
+        ROLES.save(deps.storage, &address, &roles)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("address", address)
+        .add_attribute("role", format!("{:?}", role)))
+}
+
+pub fn execute_propose_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    update: ConfigUpdate,
+) -> Result<Response<Empty>, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let governance = cfg
+        .governance
+        .clone()
+        .ok_or(ContractError::GovernanceDisabled {})?;
+
+    // same rule as for the price rounds, any member can propose and votes yes right away
+    let vote_power = is_member(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &info.sender,
+        Some(env.block.height),
+    )?
+    .ok_or(ContractError::Unauthorized {})?;
+
+    let mut prop = Proposal {
+        title: "update_config".to_string(),
+        description: "".to_string(),
+        start_height: env.block.height,
+        msgs: vec![],
+        expires: governance.voting_period.after(&env.block),
+        status: Status::Open,
+        votes: Votes::yes(vote_power),
+        threshold: governance.threshold,
+        total_weight: total_weight(deps.storage, &deps.querier, &cfg)?,
+        proposer: info.sender.clone(),
+        deposit: None,
+    };
+    prop.update_status(&env.block);
+
+    let id = GOV_PROPOSAL_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    GOV_PROPOSAL_COUNT.save(deps.storage, &id)?;
+    GOV_PROPOSALS.save(
+        deps.storage,
+        id,
+        &GovProposal {
+            proposal: prop.clone(),
+            update,
+        },
+    )?;
+    GOV_BALLOTS.save(
+        deps.storage,
+        (id, &info.sender),
+        &Ballot {
+            weight: vote_power,
+            vote: Vote::Yes,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_config_update")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+pub fn execute_vote_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: Vote,
+) -> Result<Response<Empty>, ContractError> {
+    let mut gov = GOV_PROPOSALS.load(deps.storage, proposal_id)?;
+    let prop = &mut gov.proposal;
+    if ![Status::Open, Status::Passed, Status::Rejected].contains(&prop.status) {
+        return Err(ContractError::NotOpen {});
+    }
+    if prop.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let vote_power = voting_power(deps.as_ref(), &info.sender, prop.start_height)?;
+
+    GOV_BALLOTS.update(deps.storage, (proposal_id, &info.sender), |bal| match bal {
+        Some(_) => Err(ContractError::AlreadyVoted {}),
+        None => Ok(Ballot {
+            weight: vote_power,
+            vote,
+        }),
+    })?;
+
+    prop.votes.add_vote(vote, vote_power);
+    prop.update_status(&env.block);
+    let status = prop.status;
+    GOV_PROPOSALS.save(deps.storage, proposal_id, &gov)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "vote_config_update")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", status)))
+}
+
+pub fn execute_execute_config_update(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<Empty>, ContractError> {
+    // anyone can apply a passed update, it goes through the timelock like any other
+    let mut gov = GOV_PROPOSALS.load(deps.storage, proposal_id)?;
+    gov.proposal.update_status(&env.block);
+    if gov.proposal.status != Status::Passed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+    gov.proposal.status = Status::Executed;
+    GOV_PROPOSALS.save(deps.storage, proposal_id, &gov)?;
+
+    let response = update_config(deps, &env, gov.update)?;
+    Ok(response
+        .add_attribute("action", "execute_config_update")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+/// Checks the whole config, so that an update can not leave it inconsistent
+fn validate_config(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+) -> Result<(), ContractError> {
+    let total_weight = total_weight(storage, querier, config)?;
+    let has_voters = has_voters(storage, querier, config, config.min_voters)?;
+    check_config(config, total_weight, has_voters)
+}
+
+/// Checks the config given the total weight of its members and whether enough of them can vote
+fn check_config(config: &Config, total_weight: u64, has_voters: bool) -> Result<(), ContractError> {
+    config.threshold.validate(total_weight)?;
+    // otherwise no round could ever be dispatched
+    if !has_voters {
+        return Err(ContractError::InvalidMinVoters {});
+    }
+
+    if matches!(
+        config.max_submitting_period,
+        Duration::Height(0) | Duration::Time(0)
+    ) {
+        return Err(ContractError::InvalidSubmittingPeriod {});
+    }
+
+    let mut keys = BTreeSet::new();
+    for key in &config.price_keys {
+        if key.is_empty() {
+            return Err(ContractError::EmptyPriceKey {});
+        }
+        if !keys.insert(key) {
+            return Err(ContractError::DuplicatePriceKey { key: key.clone() });
+        }
+    }
+
+    // derived keys are checked against the new price keys, which may have dropped their sources
+    for (key, derived) in &config.derived_keys {
+        if keys.contains(key)
+            || derived.sources().into_iter().any(|source| {
+                !keys.contains(source) || config.value_type(source) != ValueType::Uint128
+            })
+        {
+            return Err(ContractError::InvalidDerivedKey { key: key.clone() });
+        }
+    }
+
+    for key in config.price_key_infos.keys() {
+        if !keys.contains(key) && !config.derived_keys.contains_key(key) {
+            return Err(ContractError::UnknownPriceKey { key: key.clone() });
+        }
+    }
+
+    if let Some(governance) = &config.governance {
+        governance.threshold.validate(total_weight)?;
+        if matches!(
+            governance.voting_period,
+            Duration::Height(0) | Duration::Time(0)
+        ) {
+            return Err(ContractError::InvalidVotingPeriod {});
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_hooks(api: &dyn Api, hooks: Vec<String>) -> Result<Vec<Addr>, ContractError> {
+    hooks
+        .into_iter()
+        .map(|addr| {
+            api.addr_validate(&addr)
+                .map_err(|_| ContractError::InvalidHook { addr })
+        })
+        .collect()
+}
+
+pub fn execute_propose_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expires: Option<Expiration>,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if expires.is_some_and(|expires| expires.is_expired(&env.block)) {
+        return Err(ContractError::WrongExpiration {});
+    }
+
+    // the new owner only takes over once it accepts, so a wrong address can not lock the hub
+    let owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(
+        deps.storage,
+        &PendingOwner {
+            owner: owner.clone(),
+            expires,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_owner")
+        .add_attribute("pending_owner", owner))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<Empty>, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if pending.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(expires) = pending.expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+    }
+
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.owner = pending.owner;
+        Ok(config)
+    })?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", info.sender))
+}
+
+pub fn execute_cancel_ownership_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<Empty>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_transfer"))
+}
+
+pub fn execute_request_update(
+    deps: DepsMut,
+    info: MessageInfo,
+    keys: Vec<String>,
+) -> Result<Response<Empty>, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // anyone paying the fee can ask for a new round, otherwise only the consumers receiving the prices
+    let paid = match cfg.request_fee.as_ref() {
+        Some(fee) => {
+            let amount = must_pay(&info, &fee.denom)?;
+            if amount < fee.amount {
+                return Err(ContractError::InsufficientRequestFee {
+                    required: fee.clone(),
+                });
+            }
+            Some(Coin {
+                denom: fee.denom.clone(),
+                amount,
+            })
+        }
+        None => {
+            if info.sender != cfg.owner && !cfg.hook_contracts.contains(&info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+            None
+        }
+    };
+
+    let keys = if keys.is_empty() {
+        cfg.price_keys
+    } else {
+        if let Some(key) = keys.iter().find(|key| !cfg.price_keys.contains(key)) {
+            return Err(ContractError::UnknownPriceKey { key: key.clone() });
+        }
+        keys
+    };
+
+    // requests made while a round is open are served by that round if it gets executed
+    let mut request = UPDATE_REQUEST.may_load(deps.storage)?.unwrap_or_default();
+    for key in keys {
+        if !request.keys.contains(&key) {
+            request.keys.push(key);
+        }
+    }
+    if let Some(paid) = paid {
+        match request.fees.iter_mut().find(|fee| fee.denom == paid.denom) {
+            Some(fee) => fee.amount += paid.amount,
+            None => request.fees.push(paid),
+        }
+    }
+    UPDATE_REQUEST.save(deps.storage, &request)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "request_update")
+        .add_attribute("sender", info.sender)
+        .add_attribute("keys", request.keys.join(",")))
 }
 
 pub fn execute_propose(
@@ -142,12 +891,20 @@ pub fn execute_propose(
 
     // only members of the multisig can create a proposal
     let cfg = CONFIG.load(deps.storage)?;
+    if cfg.paused {
+        return Err(ContractError::Paused {});
+    }
 
     // verify data
     if !cfg.verify_data(&data) {
         return Err(ContractError::WrongVoteData {});
     }
 
+    // skip rounds nobody needs, unless the feeder saw the price move enough
+    if !round_due(deps.as_ref(), &env, &cfg)?.due && !has_deviated(deps.storage, &cfg, &data)? {
+        return Err(ContractError::RoundNotDue {});
+    }
+
     // Check that the native deposit was paid (as needed).
     if let Some(deposit) = cfg.proposal_deposit.as_ref() {
         deposit.check_native_deposit_paid(&info)?;
@@ -158,8 +915,15 @@ pub fn execute_propose(
     // therefore "vote", but they aren't allowed to vote otherwise.
     // Such vote is also special, because despite having 0 weight it still counts when
     // counting threshold passing
-    let vote_power = is_member(deps.storage, &deps.querier, deps.api, &info.sender, None)?
-        .ok_or(ContractError::Unauthorized {})?;
+    // The same snapshot as the voters of the round is used, the start of this block
+    let vote_power = is_member(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        &info.sender,
+        Some(env.block.height),
+    )?
+    .ok_or(ContractError::Unauthorized {})?;
 
     // max expires also used as default
     let max_expires = cfg.max_submitting_period.after(&env.block);
@@ -189,21 +953,24 @@ pub fn execute_propose(
         expires,
         status: Status::Open,
         votes: Votes::yes(vote_power), // always vote yes
-        threshold: cfg.threshold,
-        total_weight: cfg.group_addr.total_weight(&deps.querier)?,
+        threshold: cfg.threshold.clone(),
+        total_weight: total_weight(deps.storage, &deps.querier, &cfg)?,
         proposer: info.sender.clone(),
-        deposit: cfg.proposal_deposit,
+        deposit: cfg.proposal_deposit.clone(),
     };
     prop.update_status(&env.block);
+    if prop.status == Status::Passed && !cfg.has_min_voters(1) {
+        prop.status = Status::Open;
+    }
     let id = next_id(deps.storage)?;
-    PROPOSALS.save(deps.storage, id, &prop)?;
+    proposals().save(deps.storage, id, &prop)?;
 
     // add the first yes vote from voter
     let data = Data {
         weight: vote_power,
         data,
     };
-    BALLOTS.save(deps.storage, (id, &info.sender), &data)?;
+    ballots().save(deps.storage, (id, &info.sender), &data)?;
 
     Ok(Response::new()
         .add_messages(take_deposit_msg)
@@ -222,6 +989,9 @@ pub fn execute_vote(
 ) -> Result<Response<Empty>, ContractError> {
     // only members of the multisig can vote
     let cfg = CONFIG.load(deps.storage)?;
+    if cfg.paused {
+        return Err(ContractError::Paused {});
+    }
 
     // verify data
     if !cfg.verify_data(&data) {
@@ -229,7 +999,7 @@ pub fn execute_vote(
     }
 
     // ensure proposal exists and can be voted on
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
     // Allow voting on Passed and Rejected proposals too,
     if ![Status::Open, Status::Passed, Status::Rejected].contains(&prop.status) {
         return Err(ContractError::NotOpen {});
@@ -242,13 +1012,10 @@ pub fn execute_vote(
     // Only members of the multisig can submit
     // Additional check if weight >= 1
     // use a snapshot of "start of proposal"
-    let vote_power = cfg
-        .group_addr
-        .is_voting_member(&deps.querier, &info.sender, prop.start_height)?
-        .ok_or(ContractError::Unauthorized {})?;
+    let vote_power = voting_power(deps.as_ref(), &info.sender, prop.start_height)?;
 
     // cast vote if no vote previously cast
-    BALLOTS.update(deps.storage, (proposal_id, &info.sender), |bal| match bal {
+    ballots().update(deps.storage, (proposal_id, &info.sender), |bal| match bal {
         Some(_) => Err(ContractError::AlreadyVoted {}),
         None => Ok(Data {
             weight: vote_power,
@@ -259,134 +1026,621 @@ pub fn execute_vote(
     // update vote tally
     prop.votes.add_vote(Vote::Yes, vote_power);
     prop.update_status(&env.block);
+    // the weight alone is not enough, the median must come from enough distinct feeders
+    if prop.status == Status::Passed
+        && !cfg.has_min_voters(count_ballots(deps.storage, proposal_id))
+    {
+        prop.status = Status::Open;
+    }
+
+    // if passed then execute
+    let response = if prop.status == Status::Passed {
+        dispatch_round(deps.storage, &env, &cfg, proposal_id, &mut prop)?
+    } else {
+        Response::new()
+    };
 
+    proposals().save(deps.storage, proposal_id, &prop)?;
+    Ok(response
+        .add_attribute("action", "vote")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+pub fn execute_finalize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<Empty>, ContractError> {
+    // anyone can trigger this if the round passed without being executed
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.paused {
+        return Err(ContractError::Paused {});
+    }
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    prop.update_status(&env.block);
+    if prop.status != Status::Passed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+    if !cfg.has_min_voters(count_ballots(deps.storage, proposal_id)) {
+        return Err(ContractError::NotEnoughVoters {
+            min_voters: cfg.min_voters,
+        });
+    }
+
+    let response = dispatch_round(deps.storage, &env, &cfg, proposal_id, &mut prop)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    Ok(response
+        .add_attribute("action", "finalize")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+fn count_ballots(storage: &dyn Storage, proposal_id: u64) -> u64 {
+    ballots()
+        .prefix(proposal_id)
+        .keys_raw(storage, None, None, Order::Ascending)
+        .count() as u64
+}
+
+/// Aggregates the ballots of a passed round, dispatches the prices to the hooks and marks it executed
+fn dispatch_round(
+    storage: &mut dyn Storage,
+    env: &Env,
+    cfg: &Config,
+    proposal_id: u64,
+    prop: &mut Proposal,
+) -> Result<Response<Empty>, ContractError> {
     let mut response = Response::new();
 
-    // if passed then execute
-    if prop.status == Status::Passed {
-        let data_list = BALLOTS
-            .prefix(proposal_id)
-            .range(deps.storage, None, None, Order::Ascending)
-            .map(|item| Ok(item?.1.data))
-            .collect::<StdResult<Vec<_>>>()?;
+    let (feeders, ballots): (Vec<Addr>, Vec<Data>) = ballots()
+        .prefix(proposal_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+
+    let prices = aggregate_prices(cfg, prop, &ballots, &env.block, proposal_id)?;
+    // kept so the round can still be queried after the config changes
+    ROUND_PRICES.save(storage, proposal_id, &prices)?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for (price_key, price_info) in prices {
+        PRICES.save(storage, &price_key, &price_info)?;
+
+        // now create message for props.msgs and update it
+        let hook_msg = to_binary(&append_price_msg(cfg, price_key, price_info)?)?;
+        cfg.hook_contracts.iter().for_each(|addr| {
+            msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: addr.to_string(),
+                funds: vec![],
+                msg: hook_msg.clone(),
+            }));
+        });
+    }
 
-        let mut msgs: Vec<CosmosMsg> = vec![];
-        for price_key in cfg.price_keys {
-            // extract prices from each key
-            let prices = data_list
-                .iter()
-                .map(|data| data[&price_key])
-                .collect::<Vec<_>>();
+    // set it to executed
+    prop.status = Status::Executed;
+
+    // pay the requesters' fees to the feeders of this round
+    if let Some(request) = UPDATE_REQUEST.may_load(storage)? {
+        response = response.add_messages(distribute_fees(&request.fees, &feeders, &prop.proposer));
+        UPDATE_REQUEST.remove(storage);
+    }
 
-            // get price by using median
-            let median_price = calculate_median_price(prices);
-
-            // now create message for props.msgs and update it
-            cfg.hook_contracts.iter().for_each(|addr| {
-                msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: addr.to_string(),
-                    funds: vec![],
-                    msg: Binary::from(
-                        format!(
-                            r#"{{"append_price":{{"key":"{}","price":"{}","timestamp":{}}}}}"#,
-                            price_key,
-                            median_price,
-                            env.block.time.seconds()
-                        )
-                        .as_bytes(),
-                    ),
-                }));
-            });
-        }
-
-        // set it to executed
-        prop.status = Status::Executed;
-
-        // Unconditionally refund here.
-        if let Some(deposit) = &prop.deposit {
-            response = response.add_message(deposit.get_return_deposit_message(&prop.proposer)?);
+    // Unconditionally refund here.
+    if let Some(deposit) = &prop.deposit {
+        response = response.add_message(deposit.get_return_deposit_message(&prop.proposer)?);
+    };
+
+    // add msgs to response
+    Ok(response.add_messages(msgs))
+}
+
+pub fn execute_close(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<Empty>, ContractError> {
+    // anyone can trigger this if the vote passed
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    if [Status::Executed, Status::Rejected, Status::Passed].contains(&prop.status) {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    // Avoid closing of Passed due to expiration proposals, unless too few members submitted
+    if prop.current_status(&env.block) == Status::Passed
+        && cfg.has_min_voters(count_ballots(deps.storage, proposal_id))
+    {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    if !prop.expires.is_expired(&env.block) {
+        return Err(ContractError::NotExpired {});
+    }
+
+    // set it to failed
+    prop.status = Status::Rejected;
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    // Refund the deposit if we have been configured to do so.
+    let mut response = Response::new();
+    if let Some(deposit) = prop.deposit {
+        if deposit.refund_failed_proposals {
+            response = response.add_message(deposit.get_return_deposit_message(&prop.proposer)?)
+        }
+    }
+
+    Ok(response
+        .add_attribute("action", "close")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_membership_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    diffs: Vec<MemberDiff>,
+) -> Result<Response<Empty>, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let group = match &cfg.group_addr {
+        Some(group) if group.0 == info.sender => group,
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+
+    for diff in &diffs {
+        let member = deps.api.addr_validate(&diff.key)?;
+        let key = (&group.0, &member);
+        match diff.new {
+            Some(weight) => MEMBERS.save(deps.storage, key, &weight, env.block.height)?,
+            None => MEMBERS.remove(deps.storage, key, env.block.height)?,
+        }
+    }
+    let voided = void_ballots(deps.storage, &env.block, &diffs)?;
+
+    let mut response = Response::new().add_attribute("action", "member_changed_hook");
+    // attributes can not be empty
+    if !voided.is_empty() {
+        response = response.add_attribute("voided_ballots", voided.join(","));
+    }
+    Ok(response)
+}
+
+pub fn execute_sync_members(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response<Empty>, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let group = cfg.group_addr.ok_or(ContractError::NoGroup {})?;
+    let mut sync = MEMBER_SYNCS
+        .may_load(deps.storage, &group.0)?
+        .unwrap_or_default();
+    if sync.synced_at.is_some() {
+        return Err(ContractError::MembersSynced {});
+    }
+    // without the hook the copies would go stale
+    if !group
+        .hooks(&deps.querier)?
+        .contains(&env.contract.address.to_string())
+    {
+        return Err(ContractError::HookNotRegistered {});
+    }
+
+    // anyone can sync, the pages follow each other so none is skipped
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let members = group.list_members(&deps.querier, sync.last.clone(), Some(limit))?;
+    for member in &members {
+        let addr = deps.api.addr_validate(&member.addr)?;
+        MEMBERS.save(
+            deps.storage,
+            (&group.0, &addr),
+            &member.weight,
+            env.block.height,
+        )?;
+    }
+
+    let mut response = Response::new()
+        .add_attribute("action", "sync_members")
+        .add_attribute("synced", members.len().to_string());
+    if members.len() < limit as usize {
+        sync.synced_at = Some(env.block.height);
+    } else if let Some(last) = members.last() {
+        response = response.add_attribute("last_member", &last.addr);
+        sync.last = Some(last.addr.clone());
+    }
+    MEMBER_SYNCS.save(deps.storage, &group.0, &sync)?;
+
+    Ok(response)
+}
+
+pub fn execute_update_members(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    remove: Vec<String>,
+    add: Vec<Member>,
+) -> Result<Response<Empty>, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if !has_role(deps.storage, &cfg, &info.sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if cfg.group_addr.is_some() {
+        return Err(ContractError::NotNativeMembership {});
+    }
+
+    let change = ConfigChange {
+        members: Some(validate_members(deps.api, add, remove)?),
+        ..ConfigChange::default()
+    };
+    let response = apply_or_queue_change(deps.storage, &deps.querier, &env, &mut cfg, change)?;
+    // the threshold must stay reachable with the new total weight
+    validate_config(deps.storage, &deps.querier, &cfg)?;
+
+    Ok(response
+        .add_attribute("action", "update_members")
+        .add_attribute("sender", info.sender))
+}
+
+fn validate_members(
+    api: &dyn Api,
+    add: Vec<Member>,
+    remove: Vec<String>,
+) -> StdResult<MembersChange> {
+    Ok(MembersChange {
+        add: add
+            .into_iter()
+            .map(|member| Ok((api.addr_validate(&member.addr)?, member.weight)))
+            .collect::<StdResult<_>>()?,
+        remove: remove
+            .iter()
+            .map(|addr| api.addr_validate(addr))
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+/// Same order as cw4-group, additions and weight updates first, then removals
+fn update_native_members(
+    storage: &mut dyn Storage,
+    height: u64,
+    members: MembersChange,
+) -> StdResult<Vec<MemberDiff>> {
+    let mut total = NATIVE_TOTAL_WEIGHT.may_load(storage)?.unwrap_or_default();
+    let mut diffs = vec![];
+
+    for (addr, weight) in members.add {
+        let old = NATIVE_MEMBERS.may_load(storage, &addr)?;
+        NATIVE_MEMBERS.save(storage, &addr, &weight, height)?;
+        total = Uint64::new(total)
+            .checked_sub(old.unwrap_or_default().into())?
+            .checked_add(weight.into())?
+            .u64();
+        diffs.push(MemberDiff::new(addr, old, Some(weight)));
+    }
+    for addr in members.remove {
+        if let Some(old) = NATIVE_MEMBERS.may_load(storage, &addr)? {
+            NATIVE_MEMBERS.remove(storage, &addr, height)?;
+            total = Uint64::new(total).checked_sub(old.into())?.u64();
+            diffs.push(MemberDiff::new(addr, Some(old), None));
+        }
+    }
+
+    NATIVE_TOTAL_WEIGHT.save(storage, &total)?;
+    Ok(diffs)
+}
+
+/// Total weight and number of voters of the native registry once the change is applied
+fn native_members_after(storage: &dyn Storage, members: &MembersChange) -> StdResult<(u64, u32)> {
+    let mut weights = NATIVE_MEMBERS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<BTreeMap<_, _>>>()?;
+    for (addr, weight) in &members.add {
+        weights.insert(addr.clone(), *weight);
+    }
+    for addr in &members.remove {
+        weights.remove(addr);
+    }
+
+    let total = weights.values().try_fold(Uint64::zero(), |total, weight| {
+        total.checked_add(Uint64::new(*weight))
+    })?;
+    let voters = weights.values().filter(|weight| **weight >= 1).count() as u32;
+    Ok((total.u64(), voters))
+}
+
+/// Voids the ballots of the open round cast by members that were removed or lost weight, as
+/// their weight no longer backs what they submitted. The member keys must be validated already.
+fn void_ballots(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    diffs: &[MemberDiff],
+) -> StdResult<Vec<String>> {
+    // the ballots of the open round can only be voided while it is still being voted on
+    let id = last_id(storage)?;
+    let mut prop = match proposals().may_load(storage, id)? {
+        Some(prop) if prop.status == Status::Open && !prop.expires.is_expired(block) => prop,
+        _ => return Ok(vec![]),
+    };
+
+    let mut voided = vec![];
+    for diff in diffs {
+        let lost_weight = match (diff.old, diff.new) {
+            (_, None) => true,
+            (Some(old), Some(new)) => new < old,
+            (None, Some(_)) => false,
         };
+        if !lost_weight {
+            continue;
+        }
+        let member = Addr::unchecked(&diff.key);
+        if let Some(ballot) = ballots().may_load(storage, (id, &member))? {
+            ballots().remove(storage, (id, &member))?;
+            prop.votes.yes = prop.votes.yes.saturating_sub(ballot.weight);
+            voided.push(diff.key.clone());
+        }
+    }
 
-        // add msgs to response
-        response = response.add_messages(msgs);
+    if !voided.is_empty() {
+        prop.update_status(block);
+        proposals().save(storage, id, &prop)?;
+    }
+    Ok(voided)
+}
+
+/// Total weight of the group, or of the native members without one
+fn total_weight(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+) -> StdResult<u64> {
+    match &config.group_addr {
+        Some(group) => group.total_weight(querier),
+        None => Ok(NATIVE_TOTAL_WEIGHT.may_load(storage)?.unwrap_or_default()),
+    }
+}
+
+/// Whether at least `count` members have the weight to vote, only going through as many as needed
+fn has_voters(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    count: u32,
+) -> StdResult<bool> {
+    let mut voters = 0;
+    match &config.group_addr {
+        Some(group) => {
+            let mut start_after = None;
+            while voters < count {
+                let members = group.list_members(querier, start_after, Some(MAX_LIMIT))?;
+                let Some(last) = members.last() else {
+                    break;
+                };
+                start_after = Some(last.addr.clone());
+                voters += members.iter().filter(|member| member.weight >= 1).count() as u32;
+            }
+        }
+        None => {
+            for item in NATIVE_MEMBERS.range(storage, None, None, Order::Ascending) {
+                if voters >= count {
+                    break;
+                }
+                if item?.1 >= 1 {
+                    voters += 1;
+                }
+            }
+        }
+    }
+    Ok(voters >= count)
+}
+
+fn assert_last_proposal_has_done(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    if !last_proposal_has_done(deps.storage, &env.block)? {
+        return Err(ContractError::CanNotPropose {});
     }
-
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
-    Ok(response
-        .add_attribute("action", "vote")
-        .add_attribute("sender", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string())
-        .add_attribute("status", format!("{:?}", prop.status)))
+    Ok(())
 }
 
-pub fn execute_close(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    proposal_id: u64,
-) -> Result<Response<Empty>, ContractError> {
-    // anyone can trigger this if the vote passed
+fn last_proposal_has_done(storage: &dyn Storage, block: &BlockInfo) -> StdResult<bool> {
+    let last_prop_id = last_id(storage)?;
 
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    if [Status::Executed, Status::Rejected, Status::Passed].contains(&prop.status) {
-        return Err(ContractError::WrongCloseStatus {});
-    }
-    // Avoid closing of Passed due to expiration proposals
-    if prop.current_status(&env.block) == Status::Passed {
-        return Err(ContractError::WrongCloseStatus {});
-    }
-    if !prop.expires.is_expired(&env.block) {
-        return Err(ContractError::NotExpired {});
+    if last_prop_id == 0 {
+        return Ok(true);
     }
 
-    // set it to failed
-    prop.status = Status::Rejected;
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    let mut prop = proposals().load(storage, last_prop_id)?;
 
-    // Refund the deposit if we have been configured to do so.
-    let mut response = Response::new();
-    if let Some(deposit) = prop.deposit {
-        if deposit.refund_failed_proposals {
-            response = response.add_message(deposit.get_return_deposit_message(&prop.proposer)?)
+    prop.update_status(block);
+
+    Ok(matches!(prop.status, Status::Executed | Status::Rejected))
+}
+
+/// A round is due when a heartbeat has elapsed or a consumer requested one.
+/// Without any heartbeat configured, rounds are always due.
+fn round_due(deps: Deps, env: &Env, cfg: &Config) -> StdResult<RoundDueResponse> {
+    let requested_keys = UPDATE_REQUEST
+        .may_load(deps.storage)?
+        .map(|request| request.keys)
+        .unwrap_or_default();
+    let mut scheduled = false;
+    let mut heartbeat_keys = vec![];
+    for key in cfg.active_keys() {
+        if let Some(heartbeat) = cfg.feeds.get(key).and_then(|feed| feed.heartbeat) {
+            scheduled = true;
+            let elapsed = match PRICES.may_load(deps.storage, key)? {
+                Some(info) => env.block.time.seconds() >= info.timestamp.saturating_add(heartbeat),
+                None => true,
+            };
+            if elapsed {
+                heartbeat_keys.push(key.clone());
+            }
         }
     }
 
-    Ok(response
-        .add_attribute("action", "close")
-        .add_attribute("sender", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
+    let due = last_proposal_has_done(deps.storage, &env.block)?
+        && (!scheduled || !heartbeat_keys.is_empty() || !requested_keys.is_empty());
+
+    Ok(RoundDueResponse {
+        due,
+        heartbeat_keys,
+        requested_keys,
+    })
 }
 
-pub fn execute_membership_hook(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    _diffs: Vec<MemberDiff>,
-) -> Result<Response<Empty>, ContractError> {
-    // This is now a no-op
-    // But we leave the authorization check as a demo
-    let cfg = CONFIG.load(deps.storage)?;
-    if info.sender != cfg.group_addr.0 {
-        return Err(ContractError::Unauthorized {});
+/// Check if any submitted price moved past its deviation threshold since the last round
+fn has_deviated(storage: &dyn Storage, cfg: &Config, data: &VoteData) -> StdResult<bool> {
+    for key in cfg.active_keys() {
+        if let (Some(feed), Some(last)) = (cfg.feeds.get(key), PRICES.may_load(storage, key)?) {
+            if feed.has_deviated(&last.price, &data[key]) {
+                return Ok(true);
+            }
+        }
     }
-
-    Ok(Response::default())
+    Ok(false)
 }
-fn assert_last_proposal_has_done(deps: Deps, env: &Env) -> Result<(), ContractError> {
-    let last_prop_id = last_id(deps.storage)?;
 
-    if last_prop_id == 0 {
-        return Ok(());
+/// Aggregates the ballots of a round into the price of each key, then the derived keys
+fn aggregate_prices(
+    cfg: &Config,
+    prop: &Proposal,
+    ballots: &[Data],
+    block: &BlockInfo,
+    proposal_id: u64,
+) -> StdResult<Vec<(String, PriceInfo)>> {
+    let timestamp = block.time.seconds();
+    // whether a weight alone would pass the round
+    let passes = |weight: u64| {
+        Proposal {
+            votes: Votes::yes(weight),
+            ..prop.clone()
+        }
+        .is_passed(block)
+    };
+    // get price of each key by using median, or plurality for non-numeric keys
+    let mut prices = vec![];
+    for price_key in cfg.active_keys() {
+        // extract prices from each key
+        let values = ballots
+            .iter()
+            .filter_map(|ballot| Some((ballot.data.get(price_key)?, ballot.weight)))
+            .collect::<Vec<_>>();
+        let value_type = cfg.value_type(price_key);
+        // keys without any value, or a plurality of too little weight, are left out
+        if let Some((price, dispersion)) = aggregate(value_type, &values, &passes)? {
+            prices.push((
+                price_key.clone(),
+                PriceInfo {
+                    price,
+                    value_type,
+                    dispersion,
+                    timestamp,
+                    proposal_id,
+                },
+            ));
+        }
     }
 
-    let mut prop = PROPOSALS.load(deps.storage, last_prop_id)?;
+    // then the keys derived from them, skipped if they can not be computed
+    let base_prices = prices
+        .iter()
+        .filter(|(_, info)| info.value_type == ValueType::Uint128)
+        .map(|(key, info)| Ok((key.clone(), info.price.parse::<Uint128>()?)))
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, derived) in &cfg.derived_keys {
+        if let Some(price) = derived.calculate(&base_prices) {
+            prices.push((
+                key.clone(),
+                PriceInfo {
+                    price: price.into(),
+                    value_type: ValueType::Uint128,
+                    dispersion: None,
+                    timestamp,
+                    proposal_id,
+                },
+            ));
+        }
+    }
 
-    prop.update_status(&env.block);
+    Ok(prices)
+}
 
-    match prop.status {
-        Status::Executed | Status::Rejected => Ok(()),
-        _ => Err(ContractError::CanNotPropose {}),
-    }
+fn append_price_msg(cfg: &Config, key: String, price_info: PriceInfo) -> StdResult<HookMsg> {
+    // only sent on request, as consumers may reject unknown fields
+    let feed = cfg.feeds.get(&key);
+    let info = cfg
+        .price_key_infos
+        .get(&key)
+        .filter(|_| feed.is_some_and(|feed| feed.hook_info))
+        .cloned();
+    let dispersion = price_info
+        .dispersion
+        .filter(|_| feed.is_some_and(|feed| feed.hook_dispersion));
+    let PriceInfo {
+        price,
+        value_type,
+        timestamp,
+        ..
+    } = price_info;
+    Ok(match value_type {
+        ValueType::Uint128 => HookMsg::AppendPrice {
+            key,
+            price: price.parse()?,
+            timestamp,
+            info,
+            dispersion,
+        },
+        ValueType::Decimal256 => HookMsg::AppendDecimalPrice {
+            key,
+            price: price.parse()?,
+            timestamp,
+            info,
+            dispersion,
+        },
+        ValueType::Int128 => HookMsg::AppendSignedValue {
+            key,
+            value: price.parse()?,
+            timestamp,
+            info,
+            dispersion,
+        },
+        ValueType::String | ValueType::Bool | ValueType::Binary => HookMsg::AppendData {
+            key,
+            data: value_type.to_data(&price)?,
+            timestamp,
+            info,
+        },
+    })
+}
+
+/// Split the fees evenly between the feeders, the proposer also gets the remainder
+fn distribute_fees(fees: &[Coin], feeders: &[Addr], proposer: &Addr) -> Vec<BankMsg> {
+    let count = Uint128::from(feeders.len() as u128);
+    feeders
+        .iter()
+        .filter_map(|feeder| {
+            let amount = fees
+                .iter()
+                .filter_map(|fee| {
+                    let mut share = fee.amount / count;
+                    if feeder == proposer {
+                        share += fee.amount % count;
+                    }
+                    (!share.is_zero()).then(|| Coin {
+                        denom: fee.denom.clone(),
+                        amount: share,
+                    })
+                })
+                .collect::<Vec<_>>();
+            (!amount.is_empty()).then(|| BankMsg::Send {
+                to_address: feeder.to_string(),
+                amount,
+            })
+        })
+        .collect()
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -395,13 +1649,16 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Threshold {} => to_binary(&query_threshold(deps)?),
         QueryMsg::Proposal { proposal_id } => to_binary(&query_proposal(deps, env, proposal_id)?),
         QueryMsg::Vote { proposal_id, voter } => to_binary(&query_vote(deps, proposal_id, voter)?),
-        QueryMsg::ListProposals { start_after, limit } => {
-            to_binary(&list_proposals(deps, env, start_after, limit)?)
-        }
+        QueryMsg::ListProposals {
+            start_after,
+            limit,
+            filter,
+        } => to_binary(&list_proposals(deps, env, start_after, limit, filter)?),
         QueryMsg::ReverseProposals {
             start_before,
             limit,
-        } => to_binary(&reverse_proposals(deps, env, start_before, limit)?),
+            filter,
+        } => to_binary(&reverse_proposals(deps, env, start_before, limit, filter)?),
         QueryMsg::ListVotes {
             proposal_id,
             start_after,
@@ -412,13 +1669,53 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&list_voters(deps, start_after, limit)?)
         }
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&PENDING_OWNER.may_load(deps.storage)?),
+        QueryMsg::PendingConfigChanges { start_after, limit } => {
+            to_binary(&list_pending_config_changes(deps, start_after, limit)?)
+        }
+        QueryMsg::Roles { start_after, limit } => to_binary(&list_roles(deps, start_after, limit)?),
+        QueryMsg::GovernanceProposal { proposal_id } => {
+            to_binary(&query_governance_proposal(deps, env, proposal_id)?)
+        }
+        QueryMsg::ListGovernanceProposals { start_after, limit } => {
+            to_binary(&list_governance_proposals(deps, env, start_after, limit)?)
+        }
         QueryMsg::LastProposal {} => to_binary(&query_last_proposal(deps, env)),
+        QueryMsg::RoundDue {} => to_binary(&query_round_due(deps, env)?),
+        QueryMsg::Price { key } => to_binary(&query_price(deps, env, key)?),
+        QueryMsg::PriceKeyInfo { key } => to_binary(&query_price_key_info(deps, key)?),
+        QueryMsg::PreviewAggregate { proposal_id } => {
+            to_binary(&query_preview_aggregate(deps, env, proposal_id)?)
+        }
+        QueryMsg::VoterBallots {
+            voter,
+            start_after,
+            limit,
+        } => to_binary(&list_voter_ballots(deps, voter, start_after, limit)?),
+        QueryMsg::Round {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&query_round(deps, env, proposal_id, start_after, limit)?),
+        QueryMsg::FreshPrice { key } => {
+            let price = query_price(deps, env, key)?;
+            if price.stale {
+                return Err(StdError::generic_err(
+                    ContractError::StalePrice {
+                        key: price.key,
+                        timestamp: price.timestamp,
+                    }
+                    .to_string(),
+                ));
+            }
+            to_binary(&price)
+        }
     }
 }
 
 fn query_threshold(deps: Deps) -> StdResult<ThresholdResponse> {
     let cfg = CONFIG.load(deps.storage)?;
-    let total_weight = cfg.group_addr.total_weight(&deps.querier)?;
+    let total_weight = total_weight(deps.storage, &deps.querier, &cfg)?;
     Ok(cfg.threshold.to_response(total_weight))
 }
 
@@ -426,8 +1723,115 @@ fn query_config(deps: Deps) -> StdResult<Config> {
     CONFIG.load(deps.storage)
 }
 
+fn query_round_due(deps: Deps, env: Env) -> StdResult<RoundDueResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    round_due(deps, &env, &cfg)
+}
+
+fn query_price_key_info(deps: Deps, key: String) -> StdResult<PriceKeyInfo> {
+    let cfg = CONFIG.load(deps.storage)?;
+    cfg.price_key_infos
+        .get(&key)
+        .cloned()
+        .ok_or_else(|| StdError::not_found(format!("price key info of {}", key)))
+}
+
+fn query_price(deps: Deps, env: Env, key: String) -> StdResult<PriceResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let info = PRICES.load(deps.storage, &key)?;
+    let stale = info.is_stale(cfg.feeds.get(&key), env.block.time.seconds());
+
+    Ok(PriceResponse {
+        key,
+        price: info.price,
+        value_type: info.value_type,
+        dispersion: info.dispersion,
+        timestamp: info.timestamp,
+        proposal_id: info.proposal_id,
+        stale,
+    })
+}
+
+fn query_preview_aggregate(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<PreviewAggregateResponse> {
+    let prop = proposals().load(deps.storage, proposal_id)?;
+    let (_, prices) = aggregate_round(deps, &env, &prop, proposal_id)?;
+
+    Ok(PreviewAggregateResponse {
+        proposal_id,
+        status: prop.current_status(&env.block),
+        weight: prop.votes.yes,
+        threshold: prop.threshold.to_response(prop.total_weight),
+        prices,
+    })
+}
+
+fn query_round(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RoundResponse> {
+    let prop = proposals().load(deps.storage, proposal_id)?;
+    let (ballot_count, prices) = aggregate_round(deps, &env, &prop, proposal_id)?;
+
+    Ok(RoundResponse {
+        id: proposal_id,
+        status: prop.current_status(&env.block),
+        proposer: prop.proposer,
+        start_height: prop.start_height,
+        expires: prop.expires,
+        threshold: prop.threshold.to_response(prop.total_weight),
+        weight: prop.votes.yes,
+        ballot_count,
+        prices,
+        ballots: list_votes(deps, proposal_id, start_after, limit)?.votes,
+    })
+}
+
+/// Aggregates all the ballots of a round as of now, or returns what an executed round
+/// dispatched, along with how many ballots there are
+fn aggregate_round(
+    deps: Deps,
+    env: &Env,
+    prop: &Proposal,
+    proposal_id: u64,
+) -> StdResult<(u64, Vec<PriceResponse>)> {
+    let ballots = ballots()
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, ballot)| ballot))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let prices = match ROUND_PRICES.may_load(deps.storage, proposal_id)? {
+        Some(prices) => prices,
+        None => {
+            let cfg = CONFIG.load(deps.storage)?;
+            aggregate_prices(&cfg, prop, &ballots, &env.block, proposal_id)?
+        }
+    };
+    let prices = prices
+        .into_iter()
+        .map(|(key, info)| PriceResponse {
+            key,
+            price: info.price,
+            value_type: info.value_type,
+            dispersion: info.dispersion,
+            timestamp: info.timestamp,
+            proposal_id,
+            stale: false,
+        })
+        .collect();
+
+    Ok((ballots.len() as u64, prices))
+}
+
 fn query_proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse> {
-    let prop = PROPOSALS.load(deps.storage, id)?;
+    let prop = proposals().load(deps.storage, id)?;
     let status = prop.current_status(&env.block);
     let threshold = prop.threshold.to_response(prop.total_weight);
 
@@ -448,17 +1852,18 @@ fn query_proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResponse>
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+// rounds whose indexes are rebuilt per migration
+const MIGRATE_LIMIT: u32 = 100;
 
 fn list_proposals(
     deps: Deps,
     env: Env,
     start_after: Option<u64>,
     limit: Option<u32>,
+    filter: Option<ProposalFilter>,
 ) -> StdResult<ProposalListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(Bound::exclusive);
-    let proposals = PROPOSALS
-        .range(deps.storage, start, None, Order::Ascending)
+    let proposals = range_proposals(deps, &env, filter, start_after, None, Order::Ascending)?
         .take(limit)
         .map(|p| map_proposal(&env.block, p))
         .collect::<StdResult<_>>()?;
@@ -471,18 +1876,82 @@ fn reverse_proposals(
     env: Env,
     start_before: Option<u64>,
     limit: Option<u32>,
+    filter: Option<ProposalFilter>,
 ) -> StdResult<ProposalListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let end = start_before.map(Bound::exclusive);
-    let props: StdResult<Vec<_>> = PROPOSALS
-        .range(deps.storage, None, end, Order::Descending)
-        .take(limit)
-        .map(|p| map_proposal(&env.block, p))
-        .collect();
+    let props: StdResult<Vec<_>> =
+        range_proposals(deps, &env, filter, None, start_before, Order::Descending)?
+            .take(limit)
+            .map(|p| map_proposal(&env.block, p))
+            .collect();
 
     Ok(ProposalListResponse { proposals: props? })
 }
 
+type ProposalIter<'a> = Box<dyn Iterator<Item = StdResult<(u64, Proposal)>> + 'a>;
+
+/// Iterates the proposals between the exclusive id bounds that match the filter,
+/// walking the index that narrows them down the most
+fn range_proposals<'a>(
+    deps: Deps<'a>,
+    env: &Env,
+    filter: Option<ProposalFilter>,
+    start_after: Option<u64>,
+    start_before: Option<u64>,
+    order: Order,
+) -> StdResult<ProposalIter<'a>> {
+    let filter = filter.unwrap_or_default();
+    let proposer = maybe_addr(deps.api, filter.proposer)?;
+    let min_height = filter.min_height.unwrap_or_default();
+    let max_height = filter.max_height.unwrap_or(u64::MAX);
+    let (min, max) = (
+        start_after.map(Bound::exclusive),
+        start_before.map(Bound::exclusive),
+    );
+
+    // the status is matched as of now, and rounds stored as open may have passed or been
+    // rejected since, so those two can not be looked up in the index
+    let indexed_status = filter
+        .status
+        .filter(|status| ![Status::Passed, Status::Rejected].contains(status));
+
+    let idx = proposals().idx;
+    let iter = if let Some(proposer) = &proposer {
+        idx.proposer
+            .prefix(proposer.clone())
+            .range(deps.storage, min, max, order)
+    } else if let Some(status) = &indexed_status {
+        idx.status
+            .prefix(status_key(status))
+            .range(deps.storage, min, max, order)
+    } else if filter.min_height.is_some() || filter.max_height.is_some() {
+        idx.start_height.range(
+            deps.storage,
+            Some(Bound::inclusive((min_height, 0))),
+            Some(Bound::inclusive((max_height, u64::MAX))),
+            order,
+        )
+    } else {
+        proposals().range(deps.storage, min, max, order)
+    };
+
+    let block = env.block.clone();
+    Ok(Box::new(iter.filter(move |item| {
+        match item {
+            Ok((id, prop)) => {
+                start_after.is_none_or(|start| *id > start)
+                    && start_before.is_none_or(|end| *id < end)
+                    && proposer.as_ref().is_none_or(|addr| prop.proposer == *addr)
+                    && filter
+                        .status
+                        .is_none_or(|status| prop.current_status(&block) == status)
+                    && (min_height..=max_height).contains(&prop.start_height)
+            }
+            Err(_) => true,
+        }
+    })))
+}
+
 fn map_proposal(
     block: &BlockInfo,
     item: StdResult<(u64, Proposal)>,
@@ -505,9 +1974,95 @@ fn map_proposal(
     })
 }
 
+fn query_governance_proposal(
+    deps: Deps,
+    env: Env,
+    id: u64,
+) -> StdResult<GovernanceProposalResponse> {
+    let gov = GOV_PROPOSALS.load(deps.storage, id)?;
+    Ok(map_governance_proposal(&env.block, (id, gov)))
+}
+
+fn list_governance_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<GovernanceProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = GOV_PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|entry| map_governance_proposal(&env.block, entry)))
+        .collect::<StdResult<_>>()?;
+
+    Ok(GovernanceProposalListResponse { proposals })
+}
+
+fn map_governance_proposal(
+    block: &BlockInfo,
+    (id, gov): (u64, GovProposal),
+) -> GovernanceProposalResponse {
+    let status = gov.proposal.current_status(block);
+    let threshold = gov
+        .proposal
+        .threshold
+        .to_response(gov.proposal.total_weight);
+    GovernanceProposalResponse {
+        id,
+        status,
+        proposer: gov.proposal.proposer,
+        expires: gov.proposal.expires,
+        threshold,
+        update: gov.update,
+    }
+}
+
+fn list_pending_config_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingConfigChangesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let changes = PENDING_CONFIG_CHANGES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingConfigChangesResponse { changes })
+}
+
+fn list_roles(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RolesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = maybe_addr(deps.api, start_after)?;
+    let start = addr.as_ref().map(Bound::exclusive);
+
+    let roles = ROLES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, roles)| RoleInfo {
+                address: address.into(),
+                roles,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(RolesResponse { roles })
+}
+
 fn query_vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
     let voter_addr = deps.api.addr_validate(&voter)?;
-    let prop = BALLOTS.may_load(deps.storage, (proposal_id, &voter_addr))?;
+    let prop = ballots().may_load(deps.storage, (proposal_id, &voter_addr))?;
     let vote = prop.map(|b| VoteInfo { voter, data: b });
     Ok(VoteResponse { vote })
 }
@@ -522,7 +2077,7 @@ fn list_votes(
     let addr = maybe_addr(deps.api, start_after)?;
     let start = addr.as_ref().map(Bound::exclusive);
 
-    let votes = BALLOTS
+    let votes = ballots()
         .prefix(proposal_id)
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
@@ -537,9 +2092,34 @@ fn list_votes(
     Ok(VoteListResponse { votes })
 }
 
-/// Check if this address is a member and returns its weight.
-/// We dont use the group addr's is_member function because it queries using the key as &Addr, not Vec<u8> of CannonicalAddr in the latest version
-/// The current production group addr on Oraichain is using the v0.13.2 version of CosmWasm, which uses CannonicalAddr
+fn list_voter_ballots(
+    deps: Deps,
+    voter: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VoterBallotsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let voter = deps.api.addr_validate(&voter)?;
+    let start = start_after.map(|id| Bound::exclusive((id, voter.clone())));
+
+    let ballots = ballots()
+        .idx
+        .voter
+        .prefix(voter)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|((proposal_id, _), data)| VoterBallot { proposal_id, data }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(VoterBallotsResponse { ballots })
+}
+
+/// Resolves the weight of a member at the start of `height`, or its current weight without one.
+/// Without a group contract the native members are the only source. Otherwise members known to
+/// the local snapshot are answered from it, the others are looked up in the group. The weight at a
+/// height is a smart query, so the group resolves its own key layout, while the current weight is
+/// read raw from the members map, which is keyed by CanonicalAddr in the v0.13.2 group still used
+/// in production on Oraichain and by Addr since.
 fn is_member(
     storage: &dyn Storage,
     querier: &QuerierWrapper,
@@ -548,21 +2128,94 @@ fn is_member(
     height: Option<u64>,
 ) -> StdResult<Option<u64>> {
     let cfg = CONFIG.load(storage)?;
-    let mut old_ver_height = match height {
-        Some(height) => cfg
-            .group_addr
-            .member_at_height(querier, member.to_string(), height.into()),
-        None => Map::new(MEMBERS_KEY).query(
-            querier,
-            cfg.group_addr.addr(),
-            api.addr_canonicalize(member.as_str())?.to_vec(),
-        ),
-    }?;
-    // if None then we try to query using the new way
-    if old_ver_height.is_none() {
-        old_ver_height = Map::new(MEMBERS_KEY).query(querier, cfg.group_addr.addr(), member)?;
+    let group = match &cfg.group_addr {
+        Some(group) => group,
+        None => {
+            return match height {
+                Some(height) => NATIVE_MEMBERS.may_load_at_height(storage, member, height),
+                None => NATIVE_MEMBERS.may_load(storage, member),
+            }
+        }
+    };
+
+    if let Some(weight) = local_weight(storage, group, member, height)? {
+        return Ok(weight);
+    }
+
+    if let Some(height) = height {
+        // a group too old to answer at a height only has its current members to go by
+        if let Ok(weight) = group.member_at_height(querier, member.to_string(), height.into()) {
+            return Ok(weight);
+        }
+    }
+
+    let legacy: Option<u64> = Map::new(MEMBERS_KEY).query(
+        querier,
+        group.addr(),
+        api.addr_canonicalize(member.as_str())?.to_vec(),
+    )?;
+    match legacy {
+        Some(weight) => Ok(Some(weight)),
+        None => Map::new(MEMBERS_KEY).query(querier, group.addr(), member),
     }
-    Ok(old_ver_height)
+}
+
+/// Weight a member votes with on a proposal started at `start_height`, capped at its current
+/// weight so members whose ballots were voided can not vote again at full weight. Members
+/// without weight can only propose.
+fn voting_power(deps: Deps, member: &Addr, start_height: u64) -> Result<u64, ContractError> {
+    let snapshot = is_member(
+        deps.storage,
+        &deps.querier,
+        deps.api,
+        member,
+        Some(start_height),
+    )?;
+    // weight is only lost through the hook or UpdateMembers, which both keep it locally, so the
+    // group is not asked again for the current weight
+    let cfg = CONFIG.load(deps.storage)?;
+    let current = match &cfg.group_addr {
+        Some(group) => local_weight(deps.storage, group, member, None)?.unwrap_or(snapshot),
+        None => NATIVE_MEMBERS.may_load(deps.storage, member)?,
+    };
+    snapshot
+        .zip(current)
+        .map(|(snapshot, current)| snapshot.min(current))
+        .filter(|weight| *weight >= 1)
+        .ok_or(ContractError::Unauthorized {})
+}
+
+/// Weight of a group member known without asking the group, None when the group has to be asked.
+/// Members reported by the hook are known, and all of them once SyncMembers copied the group.
+fn local_weight(
+    storage: &dyn Storage,
+    group: &Cw4Contract,
+    member: &Addr,
+    height: Option<u64>,
+) -> StdResult<Option<Option<u64>>> {
+    let key = (&group.0, member);
+    let (weight, known) = match height {
+        Some(height) => {
+            let weight = MEMBERS.may_load_at_height(storage, key, height)?;
+            (weight, weight.is_some())
+        }
+        // members the hook removed are only left with their changelog
+        None => (
+            MEMBERS.may_load(storage, key)?,
+            MEMBERS
+                .changelog()
+                .prefix(key)
+                .keys_raw(storage, None, None, Order::Ascending)
+                .next()
+                .is_some(),
+        ),
+    };
+    // the snapshot holds every member from the block after the last page was copied
+    let synced = MEMBER_SYNCS
+        .may_load(storage, &group.0)?
+        .and_then(|sync| sync.synced_at)
+        .is_some_and(|synced_at| height.is_none_or(|height| height > synced_at));
+    Ok((known || synced).then_some(weight))
 }
 
 fn query_voter(deps: Deps, voter: String) -> StdResult<VoterResponse> {
@@ -578,15 +2231,31 @@ fn list_voters(
     limit: Option<u32>,
 ) -> StdResult<VoterListResponse> {
     let cfg = CONFIG.load(deps.storage)?;
-    let voters = cfg
-        .group_addr
-        .list_members(&deps.querier, start_after, limit)?
-        .into_iter()
-        .map(|member| VoterDetail {
-            addr: member.addr,
-            weight: member.weight,
-        })
-        .collect();
+    let voters = match &cfg.group_addr {
+        Some(group) => group
+            .list_members(&deps.querier, start_after, limit)?
+            .into_iter()
+            .map(|member| VoterDetail {
+                addr: member.addr,
+                weight: member.weight,
+            })
+            .collect(),
+        None => {
+            let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+            let addr = maybe_addr(deps.api, start_after)?;
+            let start = addr.as_ref().map(Bound::exclusive);
+            NATIVE_MEMBERS
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| {
+                    item.map(|(addr, weight)| VoterDetail {
+                        addr: addr.into(),
+                        weight,
+                    })
+                })
+                .collect::<StdResult<_>>()?
+        }
+    };
     Ok(VoterListResponse { voters })
 }
 
@@ -598,18 +2267,147 @@ fn query_last_proposal(deps: Deps, env: Env) -> Option<ProposalResponse> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // build the indexes for the rounds and ballots stored before they existed, a page at a time
+    let limit = msg.limit.unwrap_or(MIGRATE_LIMIT) as usize;
+    let ids = proposals()
+        .keys(
+            deps.storage,
+            msg.start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for id in &ids {
+        let prop = proposals().load(deps.storage, *id)?;
+        proposals().replace(deps.storage, *id, Some(&prop), None)?;
+        let votes = ballots()
+            .prefix(*id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (voter, ballot) in votes {
+            ballots().replace(deps.storage, (*id, &voter), Some(&ballot), None)?;
+        }
+    }
+
+    let mut response = Response::new().add_attribute("action", "migrate");
+    // a full page may be followed by more rounds
+    if ids.len() == limit {
+        if let Some(last) = ids.last() {
+            response = response.add_attribute("last_proposal_id", last.to_string());
+        }
+    }
+    Ok(response)
+}
+
+/// Aggregates the submitted values of a key with their weights according to its type
+/// Plurality values are only kept if their own weight would pass the round
+fn aggregate(
+    value_type: ValueType,
+    values: &[(&FeedValue, u64)],
+    passes: &dyn Fn(u64) -> bool,
+) -> StdResult<Option<(FeedValue, Option<Dispersion>)>> {
+    match value_type {
+        ValueType::Uint128 => median_of::<Uint128>(values),
+        ValueType::Decimal256 => median_of::<Decimal256>(values),
+        ValueType::Int128 => median_of::<Int128>(values),
+        ValueType::String | ValueType::Bool | ValueType::Binary => {
+            let values = values
+                .iter()
+                .map(|(value, weight)| Ok((value_type.normalize(value)?, *weight)))
+                .collect::<StdResult<Vec<_>>>()?;
+            Ok(calculate_plurality(values)
+                .filter(|(_, weight)| passes(*weight))
+                .map(|(value, _)| (value, None)))
+        }
+    }
+}
+
+fn median_of<T: Aggregate>(
+    values: &[(&FeedValue, u64)],
+) -> StdResult<Option<(FeedValue, Option<Dispersion>)>> {
+    let values = values
+        .iter()
+        .map(|(value, _)| value.parse())
+        .collect::<StdResult<Vec<T>>>()?;
+    let dispersion = calculate_dispersion(values.clone());
+    Ok(calculate_median_price(values).map(|median| (median.into(), dispersion)))
+}
+
+/// Returns None if there are no values or the standard deviation overflows
+pub fn calculate_dispersion<T: Aggregate>(mut values: Vec<T>) -> Option<Dispersion> {
+    values.sort();
+    let l = values.len();
+    let min = values.first()?.clone();
+    let max = values.last()?.clone();
+
+    // quartiles are the medians of the lower and upper halves
+    let (lower, upper) = if l == 1 {
+        (values.clone(), values.clone())
+    } else {
+        (values[..l >> 1].to_vec(), values[(l + 1) >> 1..].to_vec())
+    };
+    let iqr = T::spread(
+        &calculate_median_price(lower)?,
+        &calculate_median_price(upper)?,
+    );
+
+    // distances to the minimum have the same standard deviation and are never negative
+    let distances = values
+        .iter()
+        .map(|value| T::spread(&min, value).to_decimal())
+        .collect::<Vec<_>>();
+    let count = Uint256::from(l as u128);
+    let sum = distances
+        .iter()
+        .try_fold(Uint256::zero(), |sum, d| sum.checked_add(d.atomics()).ok())?;
+    let mean = Decimal256::new(sum) / count;
+    let squares = distances.iter().try_fold(Uint256::zero(), |sum, d| {
+        let diff = if *d > mean { *d - mean } else { mean - *d };
+        sum.checked_add(diff.checked_mul(diff).ok()?.atomics()).ok()
+    })?;
+    let std_dev = (Decimal256::new(squares) / count).sqrt();
+
+    Some(Dispersion {
+        min: min.into(),
+        max: max.into(),
+        iqr: iqr.into(),
+        std_dev,
+    })
+}
+
+/// The value with the most weight along with that weight, ties go to the most submitted, then to
+/// the smallest value
+pub fn calculate_plurality(values: Vec<(FeedValue, u64)>) -> Option<(FeedValue, u64)> {
+    let mut tally: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+    for (value, weight) in values {
+        let entry = tally.entry(value.0).or_default();
+        entry.0 += weight;
+        entry.1 += 1;
+    }
+    // max_by_key returns the last maximum, so iterate from the largest value
+    tally
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, (weight, _))| (FeedValue(value), weight))
 }
 
-pub fn calculate_median_price(mut prices: Vec<Uint128>) -> Uint128 {
+/// Returns None if there are no prices
+pub fn calculate_median_price<T: Aggregate>(mut prices: Vec<T>) -> Option<T> {
+    if prices.is_empty() {
+        return None;
+    }
     prices.sort();
     let l = prices.len();
     let ind = l >> 1;
     if l == ind << 1 {
         // calculate median
-        (prices[ind - 1] + prices[ind]) >> 1
+        Some(T::midpoint(&prices[ind - 1], &prices[ind]))
     } else {
-        prices[ind]
+        Some(prices.swap_remove(ind))
     }
 }
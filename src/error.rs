@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, StdError, Timestamp};
 use cw3::DepositError;
 use cw_utils::{PaymentError, ThresholdError};
 
@@ -15,6 +15,18 @@ pub enum ContractError {
     #[error("Group contract invalid address '{addr}'")]
     InvalidGroup { addr: String },
 
+    #[error("Hook contract invalid address '{addr}'")]
+    InvalidHook { addr: String },
+
+    #[error("Price keys can not be empty")]
+    EmptyPriceKey {},
+
+    #[error("Price key '{key}' is listed more than once")]
+    DuplicatePriceKey { key: String },
+
+    #[error("Max submitting period must be greater than zero")]
+    InvalidSubmittingPeriod {},
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -39,9 +51,63 @@ pub enum ContractError {
     #[error("Cannot close completed or passed proposals")]
     WrongCloseStatus {},
 
+    #[error("Proposal must have passed and not yet been executed")]
+    WrongExecuteStatus {},
+
+    #[error("Round needs ballots from at least {min_voters} members")]
+    NotEnoughVoters { min_voters: u32 },
+
+    #[error("Min voters can not exceed the number of members that can vote")]
+    InvalidMinVoters {},
+
     #[error("Last proposal must have been executed before you can propose")]
     CanNotPropose {},
 
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Oracle hub is paused")]
+    Paused {},
+
+    #[error("Config change can not be applied before {eta}")]
+    TimelockNotExpired { eta: Timestamp },
+
+    #[error("Either group_addr or members must be set, not both")]
+    InvalidMembership {},
+
+    #[error("Members are managed by the group contract")]
+    NotNativeMembership {},
+
+    #[error("Members are kept by the hub itself")]
+    NoGroup {},
+
+    #[error("The hub must be a hook of the group to sync its members")]
+    HookNotRegistered {},
+
+    #[error("Members of the group are already synced")]
+    MembersSynced {},
+
+    #[error("Governance is not enabled")]
+    GovernanceDisabled {},
+
+    #[error("Governance voting period must be greater than zero")]
+    InvalidVotingPeriod {},
+
+    #[error("No new round is due yet")]
+    RoundNotDue {},
+
+    #[error("Unknown price key '{key}'")]
+    UnknownPriceKey { key: String },
+
+    #[error("Derived key '{key}' must be new and computed from Uint128 price keys")]
+    InvalidDerivedKey { key: String },
+
+    #[error("Price of '{key}' is stale, last updated at {timestamp}")]
+    StalePrice { key: String, timestamp: u64 },
+
+    #[error("Request fee of {required} is required")]
+    InsufficientRequestFee { required: Coin },
+
     #[error("{0}")]
     Payment(#[from] PaymentError),
 
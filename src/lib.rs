@@ -26,7 +26,10 @@ pub mod contract;
 pub mod error;
 pub mod msg;
 pub mod state;
+pub mod value;
 
 pub use crate::error::ContractError;
 #[cfg(test)]
+mod testing;
+#[cfg(test)]
 mod tests;
@@ -1,18 +1,24 @@
+use cosmwasm_schema::serde::{Deserialize, Deserializer};
 use cosmwasm_schema::{cw_serde, schemars::Map, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
-use cw3::{DepositInfo, Status, UncheckedDepositInfo};
-use cw4::MemberChangedHookMsg;
+use cosmwasm_std::{Addr, Coin, Decimal256, Uint128};
+use cw3::{DepositInfo, Status, UncheckedDepositInfo, Vote};
+use cw4::{Member, MemberChangedHookMsg};
 use cw_utils::{Duration, Expiration, Threshold, ThresholdResponse};
 
-use crate::state::Data;
+use crate::state::{
+    Data, DerivedPrice, FeedConfig, GovernanceConfig, PendingConfigChange, PriceKeyInfo, Role,
+};
+use crate::value::{DataValue, Dispersion, FeedValue, Int128, ValueType};
 
-pub type VoteData = Map<String, Uint128>; // key: price
+pub type VoteData = Map<String, FeedValue>; // key: price
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: String,
     // this is the group contract that contains the member list
-    pub group_addr: String,
+    pub group_addr: Option<String>,
+    /// Members kept by the hub itself instead of a group contract, only without group_addr
+    pub members: Option<Vec<Member>>,
     pub threshold: Threshold,
     pub max_submitting_period: Duration,
 
@@ -20,7 +26,7 @@ pub struct InstantiateMsg {
     pub proposal_deposit: Option<UncheckedDepositInfo>,
 
     pub price_keys: Vec<String>,
-    pub hook_contracts: Vec<Addr>,
+    pub hook_contracts: Vec<String>,
 }
 
 // TODO: add some T variants? Maybe good enough as fixed Empty for now
@@ -38,19 +44,129 @@ pub enum ExecuteMsg {
     Close {
         proposal_id: u64,
     },
+    /// Dispatches the prices of a round that passed without being executed, e.g. once expired
+    Finalize {
+        proposal_id: u64,
+    },
     /// Handles update hook messages from the group contract
     MemberChangedHook(MemberChangedHookMsg),
-    UpdateConfig {
-        owner: Option<String>,
-        threshold: Option<Threshold>,
-        max_submitting_period: Option<Duration>,
-        price_keys: Option<Vec<String>>,
-        hook_contracts: Option<Vec<Addr>>,
+    /// Copies a page of the group members into the hub, which stops asking the group once all
+    /// of them are copied. The hub must be a hook of the group, so that they stay up to date.
+    SyncMembers {
+        limit: Option<u32>,
+    },
+    UpdateConfig(ConfigUpdate),
+    /// Switches to another cw4 group, rejecting the round and governance proposals still being
+    /// voted on. Requires the admin role and waits for the timelock.
+    UpdateGroup {
+        group_addr: String,
+    },
+    /// Adds, updates and removes members when the hub keeps them itself, requires the admin role.
+    /// Queued like the config changes while a timelock is set.
+    UpdateMembers {
+        remove: Vec<String>,
+        add: Vec<Member>,
+    },
+    /// Opens a governance proposal to update the config, voted on by the members of the group.
+    ProposeConfigUpdate {
+        update: ConfigUpdate,
+    },
+    VoteConfigUpdate {
+        proposal_id: u64,
+        vote: Vote,
+    },
+    /// Applies the update of a passed governance proposal, callable by anyone.
+    ExecuteConfigUpdate {
+        proposal_id: u64,
+    },
+    /// Asks the feeders to open a new round for the given keys, all keys if empty.
+    /// The attached fee is paid to the feeders of the next executed round.
+    RequestUpdate {
+        keys: Vec<String>,
+    },
+    /// Offers the ownership to a new address, which takes it over with AcceptOwnership.
+    /// Replaces any previous offer.
+    ProposeOwner {
+        owner: String,
+        expires: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    CancelOwnershipTransfer {},
+    /// Applies a queued config change once its timelock has passed, callable by anyone.
+    ApplyConfigChange {
+        id: u64,
+    },
+    CancelConfigChange {
+        id: u64,
+    },
+    /// Halts Propose, Vote and Finalize until Unpause, requires the pauser role.
+    Pause {},
+    Unpause {},
+    /// Leaves a key out of aggregation and hook dispatch, it is still submitted by the feeders.
+    FreezeKey {
+        key: String,
+    },
+    UnfreezeKey {
+        key: String,
+    },
+    /// Only the owner and admins can grant and revoke roles.
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+        role: Role,
     },
 }
 
+/// The fields of UpdateConfig, also the payload of a governance proposal
 #[cw_serde]
-pub struct MigrateMsg {}
+#[derive(Default)]
+pub struct ConfigUpdate {
+    pub threshold: Option<Threshold>,
+    pub max_submitting_period: Option<Duration>,
+    pub price_keys: Option<Vec<String>>,
+    pub hook_contracts: Option<Vec<String>>,
+    pub feeds: Option<Map<String, FeedConfig>>,
+    /// null removes the fee
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "double_option"
+    )]
+    pub request_fee: Option<Option<Coin>>,
+    pub derived_keys: Option<Map<String, DerivedPrice>>,
+    pub price_key_infos: Option<Map<String, PriceKeyInfo>>,
+    pub min_voters: Option<u32>,
+    /// While set, changes to the threshold, hooks and governance, removal of price keys and
+    /// lowering of the timelock are queued until applied with ApplyConfigChange
+    pub timelock: Option<u64>,
+    /// null disables governance
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "double_option"
+    )]
+    pub governance: Option<Option<GovernanceConfig>>,
+}
+
+/// Tells an explicit null, Some(None), from a missing field, None, so settings can be cleared
+pub(crate) fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// The indexes of stored rounds are rebuilt one page of rounds per migration, call it again
+/// from the returned last_proposal_id until none is returned.
+#[cw_serde]
+pub struct MigrateMsg {
+    pub start_after: Option<u64>,
+    pub limit: Option<u32>,
+}
 
 // We can also add this as a cw3 extension
 #[cw_serde]
@@ -58,17 +174,20 @@ pub struct MigrateMsg {}
 pub enum QueryMsg {
     #[returns(cw_utils::ThresholdResponse)]
     Threshold {},
+    /// Embeds at most the first 30 ballots, use Round to page through all of them.
     #[returns(cw3::ProposalResponse)]
     Proposal { proposal_id: u64 },
     #[returns(cw3::ProposalListResponse)]
     ListProposals {
         start_after: Option<u64>,
         limit: Option<u32>,
+        filter: Option<ProposalFilter>,
     },
     #[returns(cw3::ProposalListResponse)]
     ReverseProposals {
         start_before: Option<u64>,
         limit: Option<u32>,
+        filter: Option<ProposalFilter>,
     },
     #[returns(VoteResponse)]
     Vote { proposal_id: u64, voter: String },
@@ -85,11 +204,102 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Gets the ballots of a voter across rounds, oldest first.
+    #[returns(VoterBallotsResponse)]
+    VoterBallots {
+        voter: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Gets the current configuration.
     #[returns(crate::state::Config)]
     Config {},
+    /// Gets the address the ownership was offered to, if any.
+    #[returns(Option<crate::state::PendingOwner>)]
+    PendingOwner {},
+    /// Lists the config changes waiting for the timelock.
+    #[returns(PendingConfigChangesResponse)]
+    PendingConfigChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Lists the addresses holding roles besides the owner.
+    #[returns(RolesResponse)]
+    Roles {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(GovernanceProposalResponse)]
+    GovernanceProposal { proposal_id: u64 },
+    #[returns(GovernanceProposalListResponse)]
+    ListGovernanceProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     #[returns(Option<cw3::ProposalResponse>)]
     LastProposal {},
+    /// Tells feeders whether a new round can be proposed now.
+    #[returns(RoundDueResponse)]
+    RoundDue {},
+    /// Gets the last executed price of a key, flagged stale once older than its max_age.
+    #[returns(PriceResponse)]
+    Price { key: String },
+    /// Same as Price, but fails if the price is stale.
+    #[returns(PriceResponse)]
+    FreshPrice { key: String },
+    #[returns(crate::state::PriceKeyInfo)]
+    PriceKeyInfo { key: String },
+    /// Gets the prices a round would dispatch if it passed now, computed from its ballots so far.
+    #[returns(PreviewAggregateResponse)]
+    PreviewAggregate { proposal_id: u64 },
+    /// Gets a round with its aggregated values, weight totals and one page of its ballots.
+    #[returns(RoundResponse)]
+    Round {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// Sent to every hook contract for each price of an executed round
+#[cw_serde]
+pub enum HookMsg {
+    AppendPrice {
+        key: String,
+        price: Uint128,
+        timestamp: u64,
+        /// Only sent when the key has info configured and hook_info enabled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<PriceKeyInfo>,
+        /// Only sent when the key has hook_dispersion enabled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dispersion: Option<Dispersion>,
+    },
+    AppendDecimalPrice {
+        key: String,
+        price: Decimal256,
+        timestamp: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<PriceKeyInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dispersion: Option<Dispersion>,
+    },
+    AppendSignedValue {
+        key: String,
+        value: Int128,
+        timestamp: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<PriceKeyInfo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dispersion: Option<Dispersion>,
+    },
+    AppendData {
+        key: String,
+        data: DataValue,
+        timestamp: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        info: Option<PriceKeyInfo>,
+    },
 }
 
 #[cw_serde]
@@ -108,6 +318,48 @@ pub struct VoteListResponse {
     pub votes: Vec<VoteInfo>,
 }
 
+#[cw_serde]
+pub struct PendingConfigChangesResponse {
+    pub changes: Vec<PendingConfigChange>,
+}
+
+#[cw_serde]
+pub struct RoleInfo {
+    pub address: String,
+    pub roles: Vec<Role>,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub roles: Vec<RoleInfo>,
+}
+
+#[cw_serde]
+pub struct GovernanceProposalResponse {
+    pub id: u64,
+    pub status: Status,
+    pub proposer: Addr,
+    pub expires: Expiration,
+    pub threshold: ThresholdResponse,
+    pub update: ConfigUpdate,
+}
+
+#[cw_serde]
+pub struct GovernanceProposalListResponse {
+    pub proposals: Vec<GovernanceProposalResponse>,
+}
+
+#[cw_serde]
+pub struct VoterBallot {
+    pub proposal_id: u64,
+    pub data: Data,
+}
+
+#[cw_serde]
+pub struct VoterBallotsResponse {
+    pub ballots: Vec<VoterBallot>,
+}
+
 #[cw_serde]
 pub struct ProposalResponse {
     pub id: u64,
@@ -131,3 +383,62 @@ pub struct ProposalResponse {
 pub struct ProposalListResponse {
     pub proposals: Vec<ProposalResponse>,
 }
+
+#[cw_serde]
+pub struct PriceResponse {
+    pub key: String,
+    pub price: FeedValue,
+    pub value_type: ValueType,
+    pub dispersion: Option<Dispersion>,
+    pub timestamp: u64,
+    pub proposal_id: u64,
+    pub stale: bool,
+}
+
+/// Every field that is set must match
+#[cw_serde]
+#[derive(Default)]
+pub struct ProposalFilter {
+    /// Status at the current block, so an expired round already counts as Rejected
+    pub status: Option<Status>,
+    pub proposer: Option<String>,
+    /// Inclusive range of start heights
+    pub min_height: Option<u64>,
+    pub max_height: Option<u64>,
+}
+
+#[cw_serde]
+pub struct PreviewAggregateResponse {
+    pub proposal_id: u64,
+    pub status: Status,
+    /// Weight of the ballots cast so far, to compare with the threshold
+    pub weight: u64,
+    pub threshold: ThresholdResponse,
+    pub prices: Vec<PriceResponse>,
+}
+
+#[cw_serde]
+pub struct RoundResponse {
+    pub id: u64,
+    pub status: Status,
+    pub proposer: Addr,
+    pub start_height: u64,
+    pub expires: Expiration,
+    pub threshold: ThresholdResponse,
+    /// Weight of all the ballots of the round
+    pub weight: u64,
+    pub ballot_count: u64,
+    /// Values aggregated from all the ballots, computed at query time
+    pub prices: Vec<PriceResponse>,
+    /// Continue from the last voter of this page with start_after
+    pub ballots: Vec<VoteInfo>,
+}
+
+#[cw_serde]
+pub struct RoundDueResponse {
+    pub due: bool,
+    /// Keys whose heartbeat has elapsed since their last update
+    pub heartbeat_keys: Vec<String>,
+    /// Keys requested by consumers since the last executed round
+    pub requested_keys: Vec<String>,
+}
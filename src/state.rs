@@ -1,11 +1,16 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, StdResult, Storage};
-use cw3::{DepositInfo, Proposal};
+use cosmwasm_std::{Addr, Coin, StdResult, Storage, Timestamp, Uint128};
+use cw3::{Ballot, DepositInfo, Proposal, Status};
 use cw4::Cw4Contract;
-use cw_storage_plus::{Item, Map};
-use cw_utils::{Duration, Threshold};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex, SnapshotMap, Strategy,
+};
+use cw_utils::{Duration, Expiration, Threshold};
 
-use crate::msg::VoteData;
+use crate::msg::{double_option, ConfigUpdate, VoteData};
+use crate::value::{Dispersion, FeedValue, ValueType};
 
 #[cw_serde]
 pub struct Config {
@@ -13,7 +18,8 @@ pub struct Config {
     pub threshold: Threshold,
     pub max_submitting_period: Duration,
     // Total weight and voters are queried from this contract
-    pub group_addr: Cw4Contract,
+    /// None when the members are kept by the hub itself, in NATIVE_MEMBERS
+    pub group_addr: Option<Cw4Contract>,
 
     /// The price, if any, of creating a new proposal.
     pub proposal_deposit: Option<DepositInfo>,
@@ -21,23 +27,320 @@ pub struct Config {
     pub price_keys: Vec<String>,
     /// The contracts to be executed after by calling ExecuteMsg::AppendPrice { key, price, timestamp }
     pub hook_contracts: Vec<Addr>,
+
+    /// Settings per price key, keys without an entry are Uint128 and never make a round due
+    #[serde(default)]
+    pub feeds: BTreeMap<String, FeedConfig>,
+
+    /// The fee, if any, that must be attached to a RequestUpdate, paid to the feeders of the next round
+    #[serde(default)]
+    pub request_fee: Option<Coin>,
+
+    /// Keys computed from the aggregated price keys after each round
+    #[serde(default)]
+    pub derived_keys: BTreeMap<String, DerivedPrice>,
+
+    /// Describes how to read the price of each key, also sent to the hook contracts
+    #[serde(default)]
+    pub price_key_infos: BTreeMap<String, PriceKeyInfo>,
+
+    /// Distinct ballots a round needs besides the threshold weight before it is aggregated
+    #[serde(default)]
+    pub min_voters: u32,
+
+    /// Set by a pauser to halt Propose, Vote and Finalize
+    #[serde(default)]
+    pub paused: bool,
+
+    /// Keys still submitted by the feeders but left out of aggregation and dispatch
+    #[serde(default)]
+    pub frozen_keys: BTreeSet<String>,
+
+    /// Seconds that changes to the threshold, hooks or removal of price keys wait before they can be applied
+    #[serde(default)]
+    pub timelock: u64,
+
+    /// Lets the members of the group update the config through governance proposals
+    #[serde(default)]
+    pub governance: Option<GovernanceConfig>,
+}
+
+#[cw_serde]
+pub struct GovernanceConfig {
+    /// Separate from the threshold of the price rounds
+    pub threshold: Threshold,
+    pub voting_period: Duration,
 }
 
 impl Config {
+    pub fn set_price_keys(&mut self, price_keys: Vec<String>) {
+        self.frozen_keys.retain(|key| price_keys.contains(key));
+        self.price_keys = price_keys;
+    }
+
+    /// Price keys that are aggregated and dispatched
+    pub fn active_keys(&self) -> impl Iterator<Item = &String> {
+        self.price_keys
+            .iter()
+            .filter(|key| !self.frozen_keys.contains(*key))
+    }
+
     pub fn verify_data(&self, data: &VoteData) -> bool {
         // different size
         if data.keys().len() != self.price_keys.len() {
             return false;
         }
 
-        // not provide given key
+        // not provide given key or value of the wrong type or out of bounds
         for key in &self.price_keys {
-            if !data.contains_key(key) {
-                return false;
+            match data.get(key) {
+                Some(value) if self.verify_value(key, value) => {}
+                _ => return false,
             }
         }
         true
     }
+
+    pub fn verify_value(&self, key: &str, value: &FeedValue) -> bool {
+        match self.feeds.get(key) {
+            Some(feed) => {
+                feed.value_type
+                    .validate(value, feed.min_value.as_ref(), feed.max_value.as_ref())
+            }
+            None => ValueType::default().validate(value, None, None),
+        }
+    }
+
+    pub fn has_min_voters(&self, ballots: u64) -> bool {
+        ballots >= self.min_voters as u64
+    }
+
+    pub fn value_type(&self, key: &str) -> ValueType {
+        self.feeds
+            .get(key)
+            .map(|feed| feed.value_type)
+            .unwrap_or_default()
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct FeedConfig {
+    /// Seconds after the last update of this key before a new round is due
+    pub heartbeat: Option<u64>,
+    /// Price move from the last update, in basis points, that allows a round before the heartbeat
+    pub deviation_bps: Option<u64>,
+    /// Seconds after which the last price of this key is considered stale
+    pub max_age: Option<u64>,
+    /// Also send the price key info of this key to the hook contracts
+    #[serde(default)]
+    pub hook_info: bool,
+    #[serde(default)]
+    pub value_type: ValueType,
+    /// Submitted values below this bound are rejected
+    pub min_value: Option<FeedValue>,
+    /// Submitted values above this bound are rejected
+    pub max_value: Option<FeedValue>,
+    /// Also send the dispersion of the submissions to the hook contracts
+    #[serde(default)]
+    pub hook_dispersion: bool,
+}
+
+impl FeedConfig {
+    pub fn has_deviated(&self, last_price: &FeedValue, price: &FeedValue) -> bool {
+        match self.deviation_bps {
+            Some(bps) => self.value_type.has_deviated(last_price, price, bps),
+            None => false,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct PriceKeyInfo {
+    /// Number of decimals of the price, e.g. 6 means 1_000_000 is 1.0
+    pub decimals: u8,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub description: Option<String>,
+    /// Where feeders are expected to get the price from
+    pub source: Option<String>,
+}
+
+/// Prices derived from other keys, all sharing the same number of decimals
+#[cw_serde]
+pub enum DerivedPrice {
+    /// base / quote, e.g. ORAI/ATOM from ORAI/USD and ATOM/USD
+    Ratio {
+        base: String,
+        quote: String,
+        decimals: u32,
+    },
+    /// 1 / key, e.g. USD/ORAI from ORAI/USD
+    Inverse { key: String, decimals: u32 },
+}
+
+impl DerivedPrice {
+    pub fn sources(&self) -> Vec<&String> {
+        match self {
+            DerivedPrice::Ratio { base, quote, .. } => vec![base, quote],
+            DerivedPrice::Inverse { key, .. } => vec![key],
+        }
+    }
+
+    /// Returns None if a source is missing, zero or the result overflows
+    pub fn calculate(&self, prices: &[(String, Uint128)]) -> Option<Uint128> {
+        let price_of = |key: &String| {
+            prices
+                .iter()
+                .find(|(price_key, _)| price_key == key)
+                .map(|(_, price)| *price)
+        };
+        match self {
+            DerivedPrice::Ratio {
+                base,
+                quote,
+                decimals,
+            } => {
+                let one = Uint128::new(10).checked_pow(*decimals).ok()?;
+                price_of(base)?
+                    .checked_multiply_ratio(one, price_of(quote)?)
+                    .ok()
+            }
+            DerivedPrice::Inverse { key, decimals } => {
+                let one = Uint128::new(10).checked_pow(*decimals).ok()?;
+                one.checked_multiply_ratio(one, price_of(key)?).ok()
+            }
+        }
+    }
+}
+
+#[cw_serde]
+pub struct PriceInfo {
+    pub price: FeedValue,
+    #[serde(default)]
+    pub value_type: ValueType,
+    /// Only computed for numeric keys that are not derived
+    pub dispersion: Option<Dispersion>,
+    pub timestamp: u64,
+    pub proposal_id: u64,
+}
+
+impl PriceInfo {
+    pub fn is_stale(&self, feed: Option<&FeedConfig>, now: u64) -> bool {
+        match feed.and_then(|feed| feed.max_age) {
+            Some(max_age) => now > self.timestamp.saturating_add(max_age),
+            None => false,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct UpdateRequest {
+    pub keys: Vec<String>,
+    /// Fees collected from requesters, not yet paid to any round
+    pub fees: Vec<Coin>,
+}
+
+/// Changes to the config that alter what consumers trust, queued while a timelock is set
+#[cw_serde]
+#[derive(Default)]
+pub struct ConfigChange {
+    pub threshold: Option<Threshold>,
+    /// Keys added since the change was queued are kept
+    pub remove_price_keys: Vec<String>,
+    pub add_hooks: Vec<Addr>,
+    pub remove_hooks: Vec<Addr>,
+    pub timelock: Option<u64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "double_option"
+    )]
+    pub governance: Option<Option<GovernanceConfig>>,
+    pub group_addr: Option<Cw4Contract>,
+    /// Applied to NATIVE_MEMBERS rather than the config
+    pub members: Option<MembersChange>,
+}
+
+/// Native members to add or update with their weight, then to remove
+#[cw_serde]
+pub struct MembersChange {
+    pub add: Vec<(Addr, u64)>,
+    pub remove: Vec<Addr>,
+}
+
+impl ConfigChange {
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigChange::default()
+    }
+
+    pub fn apply(self, config: &mut Config) {
+        if let Some(threshold) = self.threshold {
+            config.threshold = threshold;
+        }
+        if !self.remove_price_keys.is_empty() {
+            let price_keys = config
+                .price_keys
+                .iter()
+                .filter(|key| !self.remove_price_keys.contains(key))
+                .cloned()
+                .collect();
+            config.set_price_keys(price_keys);
+        }
+        config
+            .hook_contracts
+            .retain(|hook| !self.remove_hooks.contains(hook));
+        for hook in self.add_hooks {
+            if !config.hook_contracts.contains(&hook) {
+                config.hook_contracts.push(hook);
+            }
+        }
+        if let Some(timelock) = self.timelock {
+            config.timelock = timelock;
+        }
+        if let Some(governance) = self.governance {
+            config.governance = governance;
+        }
+        if let Some(group_addr) = self.group_addr {
+            config.group_addr = Some(group_addr);
+        }
+    }
+}
+
+#[cw_serde]
+pub struct PendingConfigChange {
+    pub id: u64,
+    pub change: ConfigChange,
+    /// The change can be applied from then on
+    pub eta: Timestamp,
+}
+
+/// Administrative rights that the owner can hand out, the owner itself holds all of them
+#[cw_serde]
+#[derive(Copy, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Everything the owner can do besides transferring the ownership, including granting roles
+    Admin,
+    /// Price keys and their feeds, derived keys and infos
+    KeyManager,
+    /// Hook contracts
+    HookManager,
+    Pauser,
+}
+
+/// A config update voted on by the members of the group, separately from the price rounds
+#[cw_serde]
+pub struct GovProposal {
+    pub proposal: Proposal,
+    pub update: ConfigUpdate,
+}
+
+#[cw_serde]
+pub struct PendingOwner {
+    pub owner: Addr,
+    /// The offer can not be accepted anymore after this
+    pub expires: Option<Expiration>,
 }
 
 #[cw_serde]
@@ -62,5 +365,140 @@ pub fn last_id(store: &dyn Storage) -> StdResult<u64> {
 
 // unique items
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const BALLOTS: Map<(u64, &Addr), Data> = Map::new("votes_v2");
-pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals_v2");
+
+pub struct BallotIndexes<'a> {
+    pub voter: MultiIndex<'a, Addr, Data, (u64, Addr)>,
+}
+
+impl<'a> IndexList<Data> for BallotIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
+        let v: Vec<&dyn Index<Data>> = vec![&self.voter];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn ballots<'a>() -> IndexedMap<'a, (u64, &'a Addr), Data, BallotIndexes<'a>> {
+    let indexes = BallotIndexes {
+        // the ballot itself does not hold the voter, so take it from the primary key
+        voter: MultiIndex::new(
+            |pk, _| {
+                <(u64, Addr)>::from_slice(pk)
+                    .map(|(_, voter)| voter)
+                    .expect("ballot key holds the voter")
+            },
+            "votes_v2",
+            "votes_v2__voter",
+        ),
+    };
+    IndexedMap::new("votes_v2", indexes)
+}
+
+pub struct ProposalIndexes<'a> {
+    pub status: MultiIndex<'a, u8, Proposal, u64>,
+    pub proposer: MultiIndex<'a, Addr, Proposal, u64>,
+    pub start_height: MultiIndex<'a, u64, Proposal, u64>,
+}
+
+impl<'a> IndexList<Proposal> for ProposalIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Proposal>> + '_> {
+        let v: Vec<&dyn Index<Proposal>> = vec![&self.status, &self.proposer, &self.start_height];
+        Box::new(v.into_iter())
+    }
+}
+
+// the status index holds the stored status, an expired round keeps it until closed
+pub fn proposals<'a>() -> IndexedMap<'a, u64, Proposal, ProposalIndexes<'a>> {
+    let indexes = ProposalIndexes {
+        status: MultiIndex::new(
+            |_, prop| status_key(&prop.status),
+            "proposals_v2",
+            "proposals_v2__status",
+        ),
+        proposer: MultiIndex::new(
+            |_, prop| prop.proposer.clone(),
+            "proposals_v2",
+            "proposals_v2__proposer",
+        ),
+        start_height: MultiIndex::new(
+            |_, prop| prop.start_height,
+            "proposals_v2",
+            "proposals_v2__start_height",
+        ),
+    };
+    IndexedMap::new("proposals_v2", indexes)
+}
+
+pub fn status_key(status: &Status) -> u8 {
+    match status {
+        Status::Pending => 0,
+        Status::Open => 1,
+        Status::Rejected => 2,
+        Status::Passed => 3,
+        Status::Executed => 4,
+    }
+}
+
+// latest executed price of each key
+pub const PRICES: Map<&str, PriceInfo> = Map::new("prices");
+// prices dispatched by each executed round
+pub const ROUND_PRICES: Map<u64, Vec<(String, PriceInfo)>> = Map::new("round_prices");
+// roles granted to each address besides the owner
+pub const ROLES: Map<&Addr, Vec<Role>> = Map::new("roles");
+
+pub fn has_role(
+    storage: &dyn Storage,
+    config: &Config,
+    addr: &Addr,
+    role: Role,
+) -> StdResult<bool> {
+    if config.owner == *addr {
+        return Ok(true);
+    }
+    let roles = ROLES.may_load(storage, addr)?.unwrap_or_default();
+    Ok(roles.contains(&Role::Admin) || roles.contains(&role))
+}
+
+// config changes waiting for the timelock
+pub const CONFIG_CHANGE_COUNT: Item<u64> = Item::new("config_change_count");
+pub const PENDING_CONFIG_CHANGES: Map<u64, PendingConfigChange> =
+    Map::new("pending_config_changes");
+
+// ownership offered with ProposeOwner, until accepted or cancelled
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+pub const GOV_PROPOSAL_COUNT: Item<u64> = Item::new("gov_proposal_count");
+pub const GOV_PROPOSALS: Map<u64, GovProposal> = Map::new("gov_proposals");
+pub const GOV_BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("gov_ballots");
+// keys and fees requested by consumers since the last executed round
+pub const UPDATE_REQUEST: Item<UpdateRequest> = Item::new("update_request");
+
+/// Weights of the members of a group, reported by its MemberChangedHook or copied with SyncMembers,
+/// keyed by (group, member). Until synced, members the hook never reported are looked up in the
+/// group itself.
+pub const MEMBERS: SnapshotMap<(&Addr, &Addr), u64> = SnapshotMap::new(
+    "members",
+    "members__checkpoints",
+    "members__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Progress of copying the members of a group into MEMBERS
+#[cw_serde]
+#[derive(Default)]
+pub struct MemberSync {
+    /// Last member copied, the next page starts after it
+    pub last: Option<String>,
+    /// Height of the block the last page was copied in, MEMBERS holds every member after it
+    pub synced_at: Option<u64>,
+}
+
+pub const MEMBER_SYNCS: Map<&Addr, MemberSync> = Map::new("member_syncs");
+
+/// Members of the hub without a group contract, managed by the admins
+pub const NATIVE_MEMBERS: SnapshotMap<&Addr, u64> = SnapshotMap::new(
+    "native_members",
+    "native_members__checkpoints",
+    "native_members__changelog",
+    Strategy::EveryBlock,
+);
+pub const NATIVE_TOTAL_WEIGHT: Item<u64> = Item::new("native_total_weight");
@@ -0,0 +1,242 @@
+//! A chain for the tests, running the hub and the cw4 group from their crates with cw-multi-test.
+//! Every transaction is committed in a block of its own, 5 seconds after the previous one.
+
+use std::cell::{Cell, RefCell};
+
+use cosmwasm_schema::serde::{de::DeserializeOwned, Serialize};
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    Addr, Api, CanonicalAddr, Coin, Empty, Event, RecoverPubkeyError, StdError, StdResult,
+    VerificationError,
+};
+use cw_multi_test::{App, AppBuilder, BankKeeper, Contract, ContractWrapper, Executor};
+
+const BLOCK_TIME: u64 = 5;
+
+/// Errors are formatted with their causes, which is where the contract errors are
+pub type AppResult<T> = Result<T, String>;
+
+fn format_err(err: anyhow::Error) -> String {
+    format!("{err:#}")
+}
+
+pub fn hub_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )
+        .with_migrate(crate::contract::migrate),
+    )
+}
+
+pub fn group_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw4_group::contract::execute,
+        cw4_group::contract::instantiate,
+        cw4_group::contract::query,
+    ))
+}
+
+/// MockApi takes any lowercase string as an address, this one also rejects the spaces and
+/// punctuation that a bech32 address can not have
+pub struct TestApi(MockApi);
+
+impl Api for TestApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human)?;
+        self.0.addr_validate(human)
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        if !human.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(StdError::generic_err(format!("Invalid address {human}")));
+        }
+        self.0.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        self.0.addr_humanize(canonical)
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+pub struct Account {
+    addr: String,
+}
+
+impl Account {
+    pub fn address(&self) -> String {
+        self.addr.clone()
+    }
+}
+
+pub struct TestApp {
+    app: RefCell<App<BankKeeper, TestApi>>,
+    accounts: Cell<usize>,
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        TestApp {
+            app: RefCell::new(
+                AppBuilder::new()
+                    .with_api(TestApi(MockApi::default()))
+                    .build(|_, _, _| {}),
+            ),
+            accounts: Cell::new(0),
+        }
+    }
+}
+
+impl TestApp {
+    pub fn init_account(&self, coins: &[Coin]) -> AppResult<Account> {
+        let n = self.accounts.get();
+        self.accounts.set(n + 1);
+        let addr = format!("orai1account{n}");
+        self.app
+            .borrow_mut()
+            .init_modules(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(&addr), coins.to_vec())
+            })
+            .map_err(format_err)?;
+        Ok(Account { addr })
+    }
+
+    pub fn init_accounts(&self, coins: &[Coin], count: usize) -> AppResult<Vec<Account>> {
+        (0..count).map(|_| self.init_account(coins)).collect()
+    }
+
+    /// Moves to the next block, `seconds` later
+    pub fn increase_time(&self, seconds: u64) {
+        self.app.borrow_mut().update_block(|block| {
+            block.height += 1;
+            block.time = block.time.plus_seconds(seconds);
+        });
+    }
+}
+
+#[derive(Debug)]
+pub struct Instantiated {
+    pub address: String,
+}
+
+#[derive(Debug)]
+pub struct Response<T> {
+    pub data: T,
+    pub events: Vec<Event>,
+}
+
+pub struct Wasm<'a> {
+    app: &'a TestApp,
+}
+
+impl<'a> Wasm<'a> {
+    pub fn new(app: &'a TestApp) -> Self {
+        Wasm { app }
+    }
+
+    pub fn store_code(&self, code: Box<dyn Contract<Empty>>) -> u64 {
+        self.app.app.borrow_mut().store_code(code)
+    }
+
+    pub fn instantiate<M: Serialize>(
+        &self,
+        code_id: u64,
+        msg: &M,
+        admin: Option<&str>,
+        label: Option<&str>,
+        funds: &[Coin],
+        signer: &Account,
+    ) -> AppResult<Response<Instantiated>> {
+        let res = self.app.app.borrow_mut().instantiate_contract(
+            code_id,
+            Addr::unchecked(&signer.addr),
+            msg,
+            funds,
+            label.unwrap_or_default(),
+            admin.map(str::to_string),
+        );
+        self.app.increase_time(BLOCK_TIME);
+        let address = res.map_err(format_err)?.into_string();
+        Ok(Response {
+            data: Instantiated { address },
+            events: vec![],
+        })
+    }
+
+    pub fn execute<M: Serialize + std::fmt::Debug>(
+        &self,
+        contract: &str,
+        msg: &M,
+        funds: &[Coin],
+        signer: &Account,
+    ) -> AppResult<Response<()>> {
+        let res = self.app.app.borrow_mut().execute_contract(
+            Addr::unchecked(&signer.addr),
+            Addr::unchecked(contract),
+            msg,
+            funds,
+        );
+        self.app.increase_time(BLOCK_TIME);
+        let events = res.map_err(format_err)?.events;
+        Ok(Response { data: (), events })
+    }
+
+    pub fn query<M: Serialize, R: DeserializeOwned>(
+        &self,
+        contract: &str,
+        msg: &M,
+    ) -> AppResult<R> {
+        self.app
+            .app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(contract, msg)
+            .map_err(|err| err.to_string())
+    }
+}
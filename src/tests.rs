@@ -1,19 +1,24 @@
-use cosmwasm_std::Coin;
-use cw3::Status;
-use cw_utils::{Duration, Threshold};
-use osmosis_test_tube::{Module, OraichainTestApp, Wasm};
-use test_tube::{Account, SigningAccount};
+use std::str::FromStr;
+
+use cosmwasm_std::{Addr, Coin, Decimal, Decimal256, Uint128};
+use cw3::{Status, Vote, VoterListResponse, VoterResponse};
+use cw_utils::{Duration, Threshold, ThresholdResponse};
 
 use crate::{
-    msg::{ExecuteMsg, InstantiateMsg, ProposalResponse, QueryMsg},
-    state::Config,
+    contract::{calculate_dispersion, calculate_median_price, calculate_plurality},
+    msg::{
+        ConfigUpdate, ExecuteMsg, GovernanceProposalResponse, InstantiateMsg,
+        PendingConfigChangesResponse, PreviewAggregateResponse, PriceResponse, ProposalFilter,
+        ProposalListResponse, ProposalResponse, QueryMsg, RoleInfo, RolesResponse,
+        RoundDueResponse, RoundResponse, VoteData, VoterBallotsResponse,
+    },
+    state::{Config, DerivedPrice, FeedConfig, GovernanceConfig, PendingOwner, PriceKeyInfo, Role},
+    testing::{group_contract, hub_contract, Account, TestApp, Wasm},
+    value::{Dispersion, FeedValue, Int128, ValueType},
 };
 
-const CW4_GROUP_WASM_BYTES: &[u8] = include_bytes!("../testdata/cw4-group.wasm");
-const ORACLE_HUB_WASM_BYTES: &[u8] = include_bytes!("../testdata/cw-oracle-hub.wasm");
-
-fn init_app() -> (OraichainTestApp, Vec<SigningAccount>, String) {
-    let app = OraichainTestApp::default();
+fn init_app() -> (TestApp, Vec<Account>, String) {
+    let app = TestApp::default();
     let accounts = app
         .init_accounts(&[Coin::new(5_000_000_000_000u128, "orai")], 4)
         .unwrap();
@@ -22,11 +27,7 @@ fn init_app() -> (OraichainTestApp, Vec<SigningAccount>, String) {
         (&accounts[0], &accounts[1], &accounts[2], &accounts[3]);
     let wasm = Wasm::new(&app);
 
-    let cw4_code_id = wasm
-        .store_code(CW4_GROUP_WASM_BYTES, None, owner)
-        .unwrap()
-        .data
-        .code_id;
+    let cw4_code_id = wasm.store_code(group_contract());
 
     let cw4_group_addr = wasm
         .instantiate(
@@ -61,18 +62,15 @@ fn init_app() -> (OraichainTestApp, Vec<SigningAccount>, String) {
         .data
         .address;
 
-    let oracle_hub_code_id = wasm
-        .store_code(ORACLE_HUB_WASM_BYTES, None, owner)
-        .unwrap()
-        .data
-        .code_id;
+    let oracle_hub_code_id = wasm.store_code(hub_contract());
 
     let cw_oracle_hub_addr = wasm
         .instantiate(
             oracle_hub_code_id,
             &InstantiateMsg {
                 owner: owner.address(),
-                group_addr: cw4_group_addr.clone(),
+                group_addr: Some(cw4_group_addr.clone()),
+                members: None,
                 threshold: Threshold::AbsoluteCount { weight: 3 },
                 max_submitting_period: Duration::Time(3600),
                 proposal_deposit: None,
@@ -91,6 +89,45 @@ fn init_app() -> (OraichainTestApp, Vec<SigningAccount>, String) {
     (app, accounts, cw_oracle_hub_addr)
 }
 
+/// The first member proposes, the others vote, returns the proposal id
+fn feed_round(wasm: &Wasm, cw_oracle_hub_addr: &str, members: &[&Account], data: VoteData) -> u64 {
+    let proposal_id: u64 = wasm
+        .execute(
+            cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: data.clone(),
+                latest: None,
+            },
+            &[],
+            members[0],
+        )
+        .unwrap()
+        .events
+        .into_iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes)
+        .find(|a| a.key == "proposal_id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
+
+    for member in &members[1..] {
+        wasm.execute(
+            cw_oracle_hub_addr,
+            &ExecuteMsg::Vote {
+                proposal_id,
+                data: data.clone(),
+            },
+            &[],
+            member,
+        )
+        .unwrap();
+    }
+
+    proposal_id
+}
+
 #[test]
 fn update_price_feed() {
     let (app, accounts, cw_oracle_hub_addr) = init_app();
@@ -101,28 +138,26 @@ fn update_price_feed() {
     let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
 
     // first user propose
-    let proposal_id = u64::from_str_radix(
-        &wasm
-            .execute(
-                &cw_oracle_hub_addr,
-                &ExecuteMsg::Propose {
-                    data: [("orai".to_string(), 11_000_000u128.into())].into(),
-                    latest: None,
-                },
-                &[],
-                member0,
-            )
-            .unwrap()
-            .events
-            .into_iter()
-            .filter(|e| e.ty == "wasm")
-            .flat_map(|e| e.attributes)
-            .find(|a| a.key == "proposal_id")
-            .unwrap()
-            .value,
-        10,
-    )
-    .unwrap();
+    let proposal_id: u64 = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: [("orai".to_string(), 11_000_000u128.into())].into(),
+                latest: None,
+            },
+            &[],
+            member0,
+        )
+        .unwrap()
+        .events
+        .into_iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes)
+        .find(|a| a.key == "proposal_id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
 
     // second user vote
     wasm.execute(
@@ -202,28 +237,26 @@ fn query_last_proposal() {
     let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
 
     // first user propose
-    let proposal_id = u64::from_str_radix(
-        &wasm
-            .execute(
-                &cw_oracle_hub_addr,
-                &ExecuteMsg::Propose {
-                    data: [("orai".to_string(), 11_000_000u128.into())].into(),
-                    latest: None,
-                },
-                &[],
-                member0,
-            )
-            .unwrap()
-            .events
-            .into_iter()
-            .filter(|e| e.ty == "wasm")
-            .flat_map(|e| e.attributes)
-            .find(|a| a.key == "proposal_id")
-            .unwrap()
-            .value,
-        10,
-    )
-    .unwrap();
+    let proposal_id: u64 = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: [("orai".to_string(), 11_000_000u128.into())].into(),
+                latest: None,
+            },
+            &[],
+            member0,
+        )
+        .unwrap()
+        .events
+        .into_iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes)
+        .find(|a| a.key == "proposal_id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
 
     // second user vote
     wasm.execute(
@@ -268,13 +301,11 @@ fn update_config() {
     // try update config fail, unauthorized
     wasm.execute(
         &cw_oracle_hub_addr,
-        &ExecuteMsg::UpdateConfig {
-            owner: None,
-            threshold: None,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
             max_submitting_period: Some(Duration::Time(1200)),
             price_keys: Some(vec!["ORAI".to_string(), "ETH".to_string()]),
-            hook_contracts: None,
-        },
+            ..ConfigUpdate::default()
+        }),
         &[],
         &accounts[1],
     )
@@ -283,13 +314,24 @@ fn update_config() {
     // try update config success
     wasm.execute(
         &cw_oracle_hub_addr,
-        &ExecuteMsg::UpdateConfig {
-            owner: None,
-            threshold: None,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
             max_submitting_period: Some(Duration::Time(1200)),
             price_keys: Some(vec!["ORAI".to_string(), "ETH".to_string()]),
-            hook_contracts: None,
-        },
+            price_key_infos: Some(
+                [(
+                    "ETH".to_string(),
+                    PriceKeyInfo {
+                        decimals: 18,
+                        base_asset: "ETH".to_string(),
+                        quote_asset: "USD".to_string(),
+                        description: None,
+                        source: None,
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
         &[],
         &accounts[0],
     )
@@ -305,4 +347,2318 @@ fn update_config() {
         config.price_keys,
         vec!["ORAI".to_string(), "ETH".to_string()]
     );
+
+    let info: PriceKeyInfo = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::PriceKeyInfo {
+                key: "ETH".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(info.decimals, 18);
+
+    // info is only kept for known keys
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                price_key_infos: Some([("BTC".to_string(), info)].into()),
+                ..ConfigUpdate::default()
+            }),
+            &[],
+            &accounts[0],
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown price key 'BTC'"));
+}
+
+#[test]
+fn invalid_config() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let update = |threshold, max_submitting_period, price_keys: Option<Vec<&str>>, hooks| {
+        wasm.execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                threshold,
+                max_submitting_period,
+                price_keys: price_keys.map(|keys| keys.into_iter().map(String::from).collect()),
+                hook_contracts: hooks,
+                ..ConfigUpdate::default()
+            }),
+            &[],
+            &accounts[0],
+        )
+        .unwrap_err()
+        .to_string()
+    };
+
+    // more weight than the group has
+    assert!(update(
+        Some(Threshold::AbsoluteCount { weight: 5 }),
+        None,
+        None,
+        None
+    )
+    .contains("Not possible to reach required (passing) weight"));
+    assert!(update(None, Some(Duration::Time(0)), None, None)
+        .contains("Max submitting period must be greater than zero"));
+    assert!(
+        update(None, None, Some(vec!["orai", ""]), None).contains("Price keys can not be empty")
+    );
+    assert!(update(None, None, Some(vec!["orai", "orai"]), None)
+        .contains("Price key 'orai' is listed more than once"));
+    assert!(
+        update(None, None, None, Some(vec!["not an address".to_string()]))
+            .contains("Hook contract invalid address 'not an address'")
+    );
+}
+
+#[test]
+fn transfer_ownership() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (owner, new_owner) = (&accounts[0], &accounts[1]);
+
+    // only the owner can offer the ownership
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ProposeOwner {
+            owner: new_owner.address(),
+            expires: None,
+        },
+        &[],
+        new_owner,
+    )
+    .unwrap_err();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ProposeOwner {
+            owner: new_owner.address(),
+            expires: None,
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    let pending: Option<PendingOwner> = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::PendingOwner {})
+        .unwrap();
+    assert_eq!(pending.unwrap().owner.to_string(), new_owner.address());
+
+    // nothing changes until the offer is accepted, and only by the pending owner
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner.to_string(), owner.address());
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::AcceptOwnership {},
+        &[],
+        &accounts[2],
+    )
+    .unwrap_err();
+
+    // a cancelled offer can not be accepted
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::CancelOwnershipTransfer {},
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::AcceptOwnership {},
+        &[],
+        new_owner,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ProposeOwner {
+            owner: new_owner.address(),
+            expires: None,
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::AcceptOwnership {},
+        &[],
+        new_owner,
+    )
+    .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner.to_string(), new_owner.address());
+    let pending: Option<PendingOwner> = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::PendingOwner {})
+        .unwrap();
+    assert!(pending.is_none());
+}
+
+#[test]
+fn roles() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (owner, key_manager) = (&accounts[0], &accounts[1]);
+    let update = |threshold: Option<Threshold>, price_keys: Option<Vec<String>>| {
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            threshold,
+            price_keys,
+            ..ConfigUpdate::default()
+        })
+    };
+
+    // only admins can grant roles
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::GrantRole {
+            address: key_manager.address(),
+            role: Role::KeyManager,
+        },
+        &[],
+        key_manager,
+    )
+    .unwrap_err();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::GrantRole {
+            address: key_manager.address(),
+            role: Role::KeyManager,
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    let roles: RolesResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Roles {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        roles.roles,
+        vec![RoleInfo {
+            address: key_manager.address(),
+            roles: vec![Role::KeyManager],
+        }]
+    );
+
+    // a key manager can change the keys, but not the threshold
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update(None, Some(vec!["orai".to_string(), "eth".to_string()])),
+        &[],
+        key_manager,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update(Some(Threshold::AbsoluteCount { weight: 2 }), None),
+        &[],
+        key_manager,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::RevokeRole {
+            address: key_manager.address(),
+            role: Role::KeyManager,
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update(None, Some(vec!["orai".to_string()])),
+        &[],
+        key_manager,
+    )
+    .unwrap_err();
+    let roles: RolesResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Roles {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(roles.roles.is_empty());
+}
+
+#[test]
+fn pause_and_freeze() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (owner, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+    let data: VoteData = [
+        ("orai".to_string(), FeedValue::from(100u128)),
+        ("eth".to_string(), FeedValue::from(2000u128)),
+    ]
+    .into();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            price_keys: Some(vec!["orai".to_string(), "eth".to_string()]),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    // only pausers can pause
+    wasm.execute(&cw_oracle_hub_addr, &ExecuteMsg::Pause {}, &[], member1)
+        .unwrap_err();
+    wasm.execute(&cw_oracle_hub_addr, &ExecuteMsg::Pause {}, &[], owner)
+        .unwrap();
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: data.clone(),
+                latest: None,
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Oracle hub is paused"));
+    wasm.execute(&cw_oracle_hub_addr, &ExecuteMsg::Unpause {}, &[], owner)
+        .unwrap();
+
+    // a frozen key is still submitted, but not aggregated
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::FreezeKey {
+            key: "eth".to_string(),
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    feed_round(&wasm, &cw_oracle_hub_addr, &[owner, member1, member2], data);
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(100u128));
+    assert!(wasm
+        .query::<_, PriceResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "eth".to_string()
+            }
+        )
+        .is_err());
+
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert!(config.frozen_keys.contains("eth"));
+}
+
+#[test]
+fn timelocked_config() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let owner = &accounts[0];
+    let update = |threshold: Option<Threshold>, timelock: Option<u64>| {
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            threshold,
+            timelock,
+            ..ConfigUpdate::default()
+        })
+    };
+    let pending_changes = || {
+        wasm.query::<_, PendingConfigChangesResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::PendingConfigChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+        .changes
+    };
+
+    // raising the timelock applies at once
+    wasm.execute(&cw_oracle_hub_addr, &update(None, Some(600)), &[], owner)
+        .unwrap();
+    assert!(pending_changes().is_empty());
+
+    // the threshold change waits for it
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update(Some(Threshold::AbsoluteCount { weight: 2 }), None),
+        &[],
+        owner,
+    )
+    .unwrap();
+    let changes = pending_changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        changes[0].change.threshold,
+        Some(Threshold::AbsoluteCount { weight: 2 })
+    );
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.threshold, Threshold::AbsoluteCount { weight: 3 });
+
+    let id = changes[0].id;
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ApplyConfigChange { id },
+        &[],
+        owner,
+    )
+    .unwrap_err();
+
+    // anyone can apply it once the timelock passed
+    app.increase_time(700);
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ApplyConfigChange { id },
+        &[],
+        &accounts[3],
+    )
+    .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.threshold, Threshold::AbsoluteCount { weight: 2 });
+    assert!(pending_changes().is_empty());
+
+    // removals are queued as such, so that what is added meanwhile is kept
+    let update_keys = |price_keys: &[&str]| {
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            price_keys: Some(price_keys.iter().map(|key| key.to_string()).collect()),
+            ..ConfigUpdate::default()
+        })
+    };
+    let update_hooks = |hooks: &[&Account]| {
+        ExecuteMsg::UpdateConfig(ConfigUpdate {
+            hook_contracts: Some(hooks.iter().map(|hook| hook.address()).collect()),
+            ..ConfigUpdate::default()
+        })
+    };
+    wasm.execute(&cw_oracle_hub_addr, &update_keys(&["atom"]), &[], owner)
+        .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update_keys(&["orai", "atom", "eth"]),
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update_hooks(&[&accounts[1]]),
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &update_hooks(&[&accounts[2]]),
+        &[],
+        owner,
+    )
+    .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.price_keys, vec!["orai", "atom", "eth"]);
+    assert!(config.hook_contracts.is_empty());
+
+    app.increase_time(700);
+    for pending in pending_changes() {
+        wasm.execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::ApplyConfigChange { id: pending.id },
+            &[],
+            owner,
+        )
+        .unwrap();
+    }
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.price_keys, vec!["atom", "eth"]);
+    assert_eq!(
+        config.hook_contracts,
+        vec![
+            Addr::unchecked(accounts[1].address()),
+            Addr::unchecked(accounts[2].address())
+        ]
+    );
+
+    // lowering the timelock is queued too, and can be cancelled
+    wasm.execute(&cw_oracle_hub_addr, &update(None, Some(0)), &[], owner)
+        .unwrap();
+    let changes = pending_changes();
+    assert_eq!(changes[0].change.timelock, Some(0));
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::CancelConfigChange { id: changes[0].id },
+        &[],
+        owner,
+    )
+    .unwrap();
+    assert!(pending_changes().is_empty());
+}
+
+#[test]
+fn governance() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let propose = ExecuteMsg::ProposeConfigUpdate {
+        update: ConfigUpdate {
+            min_voters: Some(2),
+            ..Default::default()
+        },
+    };
+    let err = wasm
+        .execute(&cw_oracle_hub_addr, &propose, &[], &accounts[1])
+        .unwrap_err();
+    assert!(err.to_string().contains("Governance is not enabled"));
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            governance: Some(Some(GovernanceConfig {
+                threshold: Threshold::AbsoluteCount { weight: 2 },
+                voting_period: Duration::Time(3600),
+            })),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        &accounts[0],
+    )
+    .unwrap();
+
+    // any member can propose, voting yes at once
+    wasm.execute(&cw_oracle_hub_addr, &propose, &[], &accounts[1])
+        .unwrap();
+    let query_proposal = || {
+        wasm.query::<_, GovernanceProposalResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::GovernanceProposal { proposal_id: 1 },
+        )
+        .unwrap()
+    };
+    assert_eq!(query_proposal().status, Status::Open);
+
+    // not passed yet
+    let execute = ExecuteMsg::ExecuteConfigUpdate { proposal_id: 1 };
+    wasm.execute(&cw_oracle_hub_addr, &execute, &[], &accounts[3])
+        .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::VoteConfigUpdate {
+            proposal_id: 1,
+            vote: Vote::Yes,
+        },
+        &[],
+        &accounts[2],
+    )
+    .unwrap();
+    assert_eq!(query_proposal().status, Status::Passed);
+
+    // anyone can execute it once passed, only once
+    wasm.execute(&cw_oracle_hub_addr, &execute, &[], &accounts[3])
+        .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.min_voters, 2);
+    assert_eq!(query_proposal().status, Status::Executed);
+    wasm.execute(&cw_oracle_hub_addr, &execute, &[], &accounts[3])
+        .unwrap_err();
+
+    // governance can be disabled again
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            governance: Some(None),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        &accounts[0],
+    )
+    .unwrap();
+    let err = wasm
+        .execute(&cw_oracle_hub_addr, &propose, &[], &accounts[1])
+        .unwrap_err();
+    assert!(err.to_string().contains("Governance is not enabled"));
+}
+
+#[test]
+fn update_group() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let owner = &accounts[0];
+    let cw4_code_id = wasm.store_code(group_contract());
+    let new_group = |members: &[&Account]| {
+        wasm.instantiate(
+            cw4_code_id,
+            &cw4_group::msg::InstantiateMsg {
+                admin: Some(owner.address()),
+                members: members
+                    .iter()
+                    .map(|member| cw4::Member {
+                        addr: member.address(),
+                        weight: 1,
+                    })
+                    .collect(),
+            },
+            None,
+            Some("group"),
+            &[],
+            owner,
+        )
+        .unwrap()
+        .data
+        .address
+    };
+
+    // not a group
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateGroup {
+                group_addr: cw_oracle_hub_addr.clone(),
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Group contract invalid address"));
+
+    // too little weight for the threshold of 3
+    let small_group = new_group(&[&accounts[1], &accounts[2]]);
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateGroup {
+                group_addr: small_group,
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Not possible to reach required (passing) weight"));
+
+    // the open round was weighted by the old group
+    let mut data = VoteData::new();
+    data.insert("orai".to_string(), FeedValue::from(100u128));
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose { data, latest: None },
+        &[],
+        &accounts[1],
+    )
+    .unwrap();
+
+    let group = new_group(&[&accounts[1], &accounts[2], &accounts[3]]);
+    let update = ExecuteMsg::UpdateGroup {
+        group_addr: group.clone(),
+    };
+    wasm.execute(&cw_oracle_hub_addr, &update, &[], &accounts[1])
+        .unwrap_err();
+    wasm.execute(&cw_oracle_hub_addr, &update, &[], owner)
+        .unwrap();
+
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.group_addr.unwrap().addr().as_str(), group);
+    let prop: ProposalResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Proposal { proposal_id: 1 })
+        .unwrap();
+    assert_eq!(prop.status, Status::Rejected);
+}
+
+#[test]
+fn member_changed_hook() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let owner = &accounts[0];
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    let group_addr = config.group_addr.unwrap().addr().to_string();
+    wasm.execute(
+        &group_addr,
+        &cw4_group::msg::ExecuteMsg::AddHook {
+            addr: cw_oracle_hub_addr.clone(),
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    // only the group can call the hook
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::MemberChangedHook(cw4::MemberChangedHookMsg { diffs: vec![] }),
+        &[],
+        owner,
+    )
+    .unwrap_err();
+
+    let mut data = VoteData::new();
+    data.insert("orai".to_string(), FeedValue::from(100u128));
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: data.clone(),
+            latest: None,
+        },
+        &[],
+        &accounts[1],
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Vote {
+            proposal_id: 1,
+            data: data.clone(),
+        },
+        &[],
+        &accounts[2],
+    )
+    .unwrap();
+
+    // removing a member voids its ballot in the open round
+    wasm.execute(
+        &group_addr,
+        &cw4_group::msg::ExecuteMsg::UpdateMembers {
+            remove: vec![accounts[2].address()],
+            add: vec![cw4::Member {
+                addr: accounts[3].address(),
+                weight: 5,
+            }],
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    let round: RoundResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Round {
+                proposal_id: 1,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(round.ballot_count, 1);
+    assert_eq!(round.weight, 1);
+    assert_eq!(round.ballots[0].voter, accounts[1].address());
+
+    // the removed member can not vote again with its snapshot weight
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Vote {
+            proposal_id: 1,
+            data,
+        },
+        &[],
+        &accounts[2],
+    )
+    .unwrap_err();
+
+    // the snapshot follows the group
+    let voter: VoterResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: accounts[2].address(),
+            },
+        )
+        .unwrap();
+    assert_eq!(voter.weight, None);
+    let voter: VoterResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: accounts[3].address(),
+            },
+        )
+        .unwrap();
+    assert_eq!(voter.weight, Some(5));
+}
+
+#[test]
+fn membership_snapshot() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let owner = &accounts[0];
+    let late = app
+        .init_account(&[Coin::new(5_000_000_000_000u128, "orai")])
+        .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+
+    let mut data = VoteData::new();
+    data.insert("orai".to_string(), FeedValue::from(100u128));
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: data.clone(),
+            latest: None,
+        },
+        &[],
+        &accounts[1],
+    )
+    .unwrap();
+
+    wasm.execute(
+        config.group_addr.unwrap().addr().as_str(),
+        &cw4_group::msg::ExecuteMsg::UpdateMembers {
+            remove: vec![],
+            add: vec![cw4::Member {
+                addr: late.address(),
+                weight: 1,
+            }],
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    // a member now, but not when the round started
+    let voter: VoterResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: late.address(),
+            },
+        )
+        .unwrap();
+    assert_eq!(voter.weight, Some(1));
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Vote {
+                proposal_id: 1,
+                data,
+            },
+            &[],
+            &late,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Unauthorized"));
+}
+
+#[test]
+fn sync_members() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let owner = &accounts[0];
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    let group_addr = config.group_addr.unwrap().addr().to_string();
+    let sync = || {
+        wasm.execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::SyncMembers { limit: Some(3) },
+            &[],
+            &accounts[1],
+        )
+    };
+
+    // the copies would go stale without the hook
+    let err = sync().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("The hub must be a hook of the group to sync its members"));
+    wasm.execute(
+        &group_addr,
+        &cw4_group::msg::ExecuteMsg::AddHook {
+            addr: cw_oracle_hub_addr.clone(),
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    // a full page leaves more to sync
+    let last_member = sync()
+        .unwrap()
+        .events
+        .into_iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes)
+        .find(|a| a.key == "last_member");
+    assert!(last_member.is_some());
+    sync().unwrap();
+    let err = sync().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Members of the group are already synced"));
+
+    // from then on the group is not asked, so a change it does not report goes unseen
+    wasm.execute(
+        &group_addr,
+        &cw4_group::msg::ExecuteMsg::RemoveHook {
+            addr: cw_oracle_hub_addr.clone(),
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &group_addr,
+        &cw4_group::msg::ExecuteMsg::UpdateMembers {
+            remove: vec![accounts[3].address()],
+            add: vec![],
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    let voter: VoterResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: accounts[3].address(),
+            },
+        )
+        .unwrap();
+    assert_eq!(voter.weight, Some(1));
+
+    let mut data = VoteData::new();
+    data.insert("orai".to_string(), FeedValue::from(100u128));
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[&accounts[1], &accounts[2], &accounts[3]],
+        data,
+    );
+}
+
+#[test]
+fn native_members() {
+    let app = TestApp::default();
+    let accounts = app
+        .init_accounts(&[Coin::new(5_000_000_000_000u128, "orai")], 4)
+        .unwrap();
+    let owner = &accounts[0];
+    let wasm = Wasm::new(&app);
+
+    let oracle_hub_code_id = wasm.store_code(hub_contract());
+    let instantiate = |members: Option<Vec<cw4::Member>>| {
+        wasm.instantiate(
+            oracle_hub_code_id,
+            &InstantiateMsg {
+                owner: owner.address(),
+                group_addr: None,
+                members,
+                threshold: Threshold::AbsoluteCount { weight: 2 },
+                max_submitting_period: Duration::Time(3600),
+                proposal_deposit: None,
+                price_keys: vec!["orai".to_string()],
+                hook_contracts: vec![],
+            },
+            None,
+            Some("oracle-hub"),
+            &[],
+            owner,
+        )
+    };
+
+    // either a group or members
+    let err = instantiate(None).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Either group_addr or members must be set, not both"));
+
+    let cw_oracle_hub_addr = instantiate(Some(
+        accounts[..3]
+            .iter()
+            .map(|account| cw4::Member {
+                addr: account.address(),
+                weight: 1,
+            })
+            .collect(),
+    ))
+    .unwrap()
+    .data
+    .address;
+
+    let voters: VoterListResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::ListVoters {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(voters.voters.len(), 3);
+    let threshold: ThresholdResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Threshold {})
+        .unwrap();
+    assert_eq!(
+        threshold,
+        ThresholdResponse::AbsoluteCount {
+            weight: 2,
+            total_weight: 3
+        }
+    );
+
+    let mut data = VoteData::new();
+    data.insert("orai".to_string(), FeedValue::from(100u128));
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[&accounts[1], &accounts[2]],
+        data,
+    );
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(100u128));
+
+    // only admins manage the members
+    let update = ExecuteMsg::UpdateMembers {
+        remove: vec![accounts[2].address()],
+        add: vec![cw4::Member {
+            addr: accounts[3].address(),
+            weight: 2,
+        }],
+    };
+    wasm.execute(&cw_oracle_hub_addr, &update, &[], &accounts[1])
+        .unwrap_err();
+    wasm.execute(&cw_oracle_hub_addr, &update, &[], owner)
+        .unwrap();
+    let voter: VoterResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: accounts[3].address(),
+            },
+        )
+        .unwrap();
+    assert_eq!(voter.weight, Some(2));
+
+    // the threshold must stay reachable
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateMembers {
+                remove: vec![accounts[0].address(), accounts[3].address()],
+                add: vec![],
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Not possible to reach required (passing) weight"));
+
+    // member updates wait for the timelock
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            timelock: Some(600),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        owner,
+    )
+    .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateMembers {
+            remove: vec![],
+            add: vec![cw4::Member {
+                addr: accounts[0].address(),
+                weight: 5,
+            }],
+        },
+        &[],
+        owner,
+    )
+    .unwrap();
+    let weight = || {
+        wasm.query::<_, VoterResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Voter {
+                address: accounts[0].address(),
+            },
+        )
+        .unwrap()
+        .weight
+    };
+    assert_eq!(weight(), Some(1));
+
+    // queued updates are checked against the members they leave
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateMembers {
+                remove: vec![accounts[0].address(), accounts[3].address()],
+                add: vec![],
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Not possible to reach required (passing) weight"));
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateMembers {
+                remove: vec![],
+                add: vec![cw4::Member {
+                    addr: accounts[2].address(),
+                    weight: u64::MAX,
+                }],
+            },
+            &[],
+            owner,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Overflow"));
+
+    let changes = wasm
+        .query::<_, PendingConfigChangesResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::PendingConfigChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+        .changes;
+    assert_eq!(changes.len(), 1);
+    app.increase_time(700);
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::ApplyConfigChange { id: changes[0].id },
+        &[],
+        owner,
+    )
+    .unwrap();
+    assert_eq!(weight(), Some(5));
+}
+
+#[test]
+fn round_due() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    // orai must be refreshed every 10 minutes or after a 1% move
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            feeds: Some(
+                [(
+                    "orai".to_string(),
+                    FeedConfig {
+                        heartbeat: Some(600),
+                        deviation_bps: Some(100),
+                        max_age: None,
+                        hook_info: false,
+                        value_type: ValueType::Uint128,
+                        min_value: None,
+                        max_value: None,
+                        hook_dispersion: false,
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    // never updated, so the first round is due
+    let round_due: RoundDueResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::RoundDue {})
+        .unwrap();
+    assert!(round_due.due);
+    assert_eq!(round_due.heartbeat_keys, vec!["orai".to_string()]);
+
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [("orai".to_string(), 11_000_000u128.into())].into(),
+    );
+
+    let round_due: RoundDueResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::RoundDue {})
+        .unwrap();
+    assert!(!round_due.due);
+
+    // same price before the heartbeat is rejected
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: [("orai".to_string(), 11_050_000u128.into())].into(),
+                latest: None,
+            },
+            &[],
+            member0,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("No new round is due yet"));
+
+    // a 1% move is accepted
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: [("orai".to_string(), 11_110_000u128.into())].into(),
+            latest: None,
+        },
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    // once the round expires and the heartbeat elapsed, a new round is due again
+    app.increase_time(3700);
+    let round_due: RoundDueResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::RoundDue {})
+        .unwrap();
+    assert!(round_due.due);
+}
+
+#[test]
+fn deviation() {
+    let deviated = |last: u128, value: u128| {
+        ValueType::Uint128.has_deviated(&FeedValue::from(last), &FeedValue::from(value), 100)
+    };
+    assert!(deviated(100, 101));
+    assert!(!deviated(100, 100));
+    // a zero price only deviates once it moves
+    assert!(!deviated(0, 0));
+    assert!(deviated(0, 1));
+}
+
+#[test]
+fn request_update() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (owner, consumer) = (&accounts[0], &accounts[3]);
+
+    // without a request fee, only the owner and hook contracts can request
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::RequestUpdate { keys: vec![] },
+        &[],
+        consumer,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            request_fee: Some(Some(Coin::new(1_000_000, "orai"))),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        owner,
+    )
+    .unwrap();
+
+    // fee too low
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::RequestUpdate {
+                keys: vec!["orai".to_string()],
+            },
+            &[Coin::new(1_000, "orai")],
+            consumer,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Request fee of 1000000orai is required"));
+
+    // unknown key
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::RequestUpdate {
+            keys: vec!["eth".to_string()],
+        },
+        &[Coin::new(1_000_000, "orai")],
+        consumer,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::RequestUpdate {
+            keys: vec!["orai".to_string()],
+        },
+        &[Coin::new(1_000_000, "orai")],
+        consumer,
+    )
+    .unwrap();
+
+    let round_due: RoundDueResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::RoundDue {})
+        .unwrap();
+    assert!(round_due.due);
+    assert_eq!(round_due.requested_keys, vec!["orai".to_string()]);
+
+    // the fee can be removed again
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            request_fee: Some(None),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        owner,
+    )
+    .unwrap();
+    let config: Config = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.request_fee, None);
+}
+
+#[test]
+fn stale_price() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            feeds: Some(
+                [(
+                    "orai".to_string(),
+                    FeedConfig {
+                        heartbeat: None,
+                        deviation_bps: None,
+                        max_age: Some(600),
+                        hook_info: false,
+                        value_type: ValueType::Uint128,
+                        min_value: None,
+                        max_value: None,
+                        hook_dispersion: false,
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    let proposal_id = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [("orai".to_string(), 11_000_000u128.into())].into(),
+    );
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::FreshPrice {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(11_000_000u128));
+    assert_eq!(price.proposal_id, proposal_id);
+    assert!(!price.stale);
+
+    app.increase_time(700);
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert!(price.stale);
+
+    wasm.query::<_, PriceResponse>(
+        &cw_oracle_hub_addr,
+        &QueryMsg::FreshPrice {
+            key: "orai".to_string(),
+        },
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn derived_price() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    // source must be a price key
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            derived_keys: Some(
+                [(
+                    "usd/atom".to_string(),
+                    DerivedPrice::Inverse {
+                        key: "atom".to_string(),
+                        decimals: 6,
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            derived_keys: Some(
+                [(
+                    "usd/orai".to_string(),
+                    DerivedPrice::Inverse {
+                        key: "orai".to_string(),
+                        decimals: 6,
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [("orai".to_string(), 2_000_000u128.into())].into(),
+    );
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "usd/orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(500_000u128));
+}
+
+#[test]
+fn preview_aggregate() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    // two of the three ballots needed
+    let proposal_id: u64 = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Propose {
+                data: [("orai".to_string(), FeedValue::from(100u128))].into(),
+                latest: None,
+            },
+            &[],
+            member0,
+        )
+        .unwrap()
+        .events
+        .into_iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes)
+        .find(|a| a.key == "proposal_id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Vote {
+            proposal_id,
+            data: [("orai".to_string(), FeedValue::from(200u128))].into(),
+        },
+        &[],
+        member1,
+    )
+    .unwrap();
+
+    let preview: PreviewAggregateResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::PreviewAggregate { proposal_id },
+        )
+        .unwrap();
+    assert_eq!(preview.status, Status::Open);
+    assert_eq!(preview.weight, 2);
+    assert_eq!(
+        preview.threshold,
+        ThresholdResponse::AbsoluteCount {
+            weight: 3,
+            total_weight: 4
+        }
+    );
+    assert_eq!(preview.prices.len(), 1);
+    assert_eq!(preview.prices[0].price, FeedValue::from(150u128));
+
+    // nothing is dispatched until the round passes
+    assert!(wasm
+        .query::<_, PriceResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string()
+            }
+        )
+        .is_err());
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Vote {
+            proposal_id,
+            data: [("orai".to_string(), FeedValue::from(300u128))].into(),
+        },
+        &[],
+        member2,
+    )
+    .unwrap();
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(200u128));
+}
+
+#[test]
+fn round_ballots() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let members = [&accounts[0], &accounts[1], &accounts[2]];
+    let proposal_id = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &members,
+        [("orai".to_string(), FeedValue::from(100u128))].into(),
+    );
+
+    let round: RoundResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Round {
+                proposal_id,
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(round.status, Status::Executed);
+    assert_eq!(round.weight, 3);
+    assert_eq!(round.ballot_count, 3);
+    assert_eq!(round.prices[0].price, FeedValue::from(100u128));
+    assert_eq!(round.ballots.len(), 2);
+
+    // the next page holds the remaining ballot
+    let next: RoundResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Round {
+                proposal_id,
+                start_after: Some(round.ballots[1].voter.clone()),
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(next.ballots.len(), 1);
+    assert!(round
+        .ballots
+        .iter()
+        .all(|ballot| ballot.voter != next.ballots[0].voter));
+
+    // an executed round keeps what it dispatched when the keys change
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            price_keys: Some(vec!["atom".to_string()]),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        &accounts[0],
+    )
+    .unwrap();
+    let round: RoundResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Round {
+                proposal_id,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(round.prices.len(), 1);
+    assert_eq!(round.prices[0].key, "orai");
+    assert_eq!(round.prices[0].price, FeedValue::from(100u128));
+}
+
+#[test]
+fn filter_proposals() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+    let data: VoteData = [("orai".to_string(), FeedValue::from(100u128))].into();
+    let first = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        data.clone(),
+    );
+    let second = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member1, member0, member2],
+        data.clone(),
+    );
+    // left open
+    let third = feed_round(&wasm, &cw_oracle_hub_addr, &[member1], data);
+
+    let list = |filter: ProposalFilter| -> Vec<u64> {
+        wasm.query::<_, ProposalListResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::ListProposals {
+                start_after: None,
+                limit: None,
+                filter: Some(filter),
+            },
+        )
+        .unwrap()
+        .proposals
+        .into_iter()
+        .map(|prop| prop.id)
+        .collect()
+    };
+
+    assert_eq!(
+        list(ProposalFilter {
+            proposer: Some(member1.address()),
+            ..ProposalFilter::default()
+        }),
+        vec![second, third]
+    );
+    assert_eq!(
+        list(ProposalFilter {
+            status: Some(Status::Executed),
+            ..ProposalFilter::default()
+        }),
+        vec![first, second]
+    );
+    assert_eq!(
+        list(ProposalFilter {
+            status: Some(Status::Executed),
+            proposer: Some(member1.address()),
+            ..ProposalFilter::default()
+        }),
+        vec![second]
+    );
+
+    let start_height = wasm
+        .query::<_, ProposalResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Proposal {
+                proposal_id: second,
+            },
+        )
+        .unwrap()
+        .updated_at;
+    assert_eq!(
+        list(ProposalFilter {
+            min_height: Some(start_height),
+            ..ProposalFilter::default()
+        }),
+        vec![second, third]
+    );
+
+    // newest first
+    let reversed = wasm
+        .query::<_, ProposalListResponse>(
+            &cw_oracle_hub_addr,
+            &QueryMsg::ReverseProposals {
+                start_before: Some(third),
+                limit: None,
+                filter: Some(ProposalFilter {
+                    max_height: Some(start_height),
+                    ..ProposalFilter::default()
+                }),
+            },
+        )
+        .unwrap()
+        .proposals
+        .into_iter()
+        .map(|prop| prop.id)
+        .collect::<Vec<_>>();
+    assert_eq!(reversed, vec![second, first]);
+
+    // the open round is rejected once it expires, even though it is stored as open
+    app.increase_time(3700);
+    assert_eq!(
+        list(ProposalFilter {
+            status: Some(Status::Rejected),
+            ..ProposalFilter::default()
+        }),
+        vec![third]
+    );
+    assert_eq!(
+        list(ProposalFilter {
+            status: Some(Status::Open),
+            ..ProposalFilter::default()
+        }),
+        Vec::<u64>::new()
+    );
+}
+
+#[test]
+fn voter_ballots() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+    let first = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [("orai".to_string(), FeedValue::from(100u128))].into(),
+    );
+    let second = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member2, member1],
+        [("orai".to_string(), FeedValue::from(200u128))].into(),
+    );
+
+    let ballots: VoterBallotsResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::VoterBallots {
+                voter: member2.address(),
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(ballots.ballots.len(), 1);
+    assert_eq!(ballots.ballots[0].proposal_id, first);
+    assert_eq!(
+        ballots.ballots[0].data.data["orai"],
+        FeedValue::from(100u128)
+    );
+
+    let ballots: VoterBallotsResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::VoterBallots {
+                voter: member2.address(),
+                start_after: Some(first),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(ballots.ballots.len(), 1);
+    assert_eq!(ballots.ballots[0].proposal_id, second);
+    assert_eq!(
+        ballots.ballots[0].data.data["orai"],
+        FeedValue::from(200u128)
+    );
+
+    // a member who never voted has no history
+    let ballots: VoterBallotsResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::VoterBallots {
+                voter: accounts[3].address(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(ballots.ballots.is_empty());
+}
+
+#[test]
+fn finalize_round() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1) = (&accounts[0], &accounts[1]);
+
+    // a quorum of one member is enough once the round expired
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            threshold: Some(Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(25),
+            }),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    let proposal_id = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member1],
+        [("orai".to_string(), FeedValue::from(100u128))].into(),
+    );
+
+    // still open, nothing to finalize
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Finalize { proposal_id },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    app.increase_time(3700);
+    let proposal: ProposalResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Proposal { proposal_id })
+        .unwrap();
+    assert_eq!(proposal.status, Status::Passed);
+
+    // anyone can finalize it
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Finalize { proposal_id },
+        &[],
+        &accounts[3],
+    )
+    .unwrap();
+    let proposal: ProposalResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Proposal { proposal_id })
+        .unwrap();
+    assert_eq!(proposal.status, Status::Executed);
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue::from(100u128));
+
+    // only once
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Finalize { proposal_id },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn min_voters() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    // more than the four members of the group
+    let err = wasm
+        .execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::UpdateConfig(ConfigUpdate {
+                min_voters: Some(5),
+                ..ConfigUpdate::default()
+            }),
+            &[],
+            member0,
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Min voters can not exceed the number of members that can vote"));
+
+    // two members have enough weight, but three must submit
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            threshold: Some(Threshold::AbsoluteCount { weight: 2 }),
+            min_voters: Some(3),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    let data: VoteData = [("orai".to_string(), FeedValue::from(100u128))].into();
+    let proposal_id = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1],
+        data.clone(),
+    );
+    let proposal: ProposalResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Proposal { proposal_id })
+        .unwrap();
+    // the weight is met, but the round waits for a third ballot
+    assert_eq!(proposal.status, Status::Passed);
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Finalize { proposal_id },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Vote { proposal_id, data },
+        &[],
+        member2,
+    )
+    .unwrap();
+    let proposal: ProposalResponse = wasm
+        .query(&cw_oracle_hub_addr, &QueryMsg::Proposal { proposal_id })
+        .unwrap();
+    assert_eq!(proposal.status, Status::Executed);
+}
+
+#[test]
+fn median_price() {
+    assert_eq!(
+        calculate_median_price(vec![Uint128::new(3), Uint128::new(1), Uint128::new(2)]),
+        Some(Uint128::new(2))
+    );
+    // integer median rounds down
+    assert_eq!(
+        calculate_median_price(vec![Uint128::new(1), Uint128::new(2)]),
+        Some(Uint128::new(1))
+    );
+    // decimal median keeps the half unit
+    assert_eq!(
+        calculate_median_price(vec![
+            Decimal256::from_str("0.000001").unwrap(),
+            Decimal256::from_str("0.000002").unwrap()
+        ]),
+        Some(Decimal256::from_str("0.0000015").unwrap())
+    );
+    // halving before adding can not overflow
+    assert_eq!(
+        calculate_median_price(vec![Uint128::MAX, Uint128::MAX - Uint128::one()]),
+        Some(Uint128::MAX - Uint128::one())
+    );
+    assert_eq!(
+        calculate_median_price(vec![Decimal256::MAX, Decimal256::MAX]),
+        Some(Decimal256::MAX)
+    );
+    // no median without prices
+    assert_eq!(calculate_median_price(Vec::<Uint128>::new()), None);
+}
+
+#[test]
+fn dispersion() {
+    let values = [2u128, 4, 4, 4, 5, 5, 7, 9].map(Uint128::new).to_vec();
+    assert_eq!(
+        calculate_dispersion(values),
+        Some(Dispersion {
+            min: 2u128.into(),
+            max: 9u128.into(),
+            iqr: 2u128.into(),
+            std_dev: Decimal256::from_ratio(2u8, 1u8),
+        })
+    );
+    // a single submission does not disagree with itself
+    assert_eq!(
+        calculate_dispersion(vec![Int128::new(-5)]),
+        Some(Dispersion {
+            min: Int128::new(-5).into(),
+            max: Int128::new(-5).into(),
+            iqr: Int128::new(0).into(),
+            std_dev: Decimal256::zero(),
+        })
+    );
+    assert_eq!(calculate_dispersion::<Uint128>(vec![]), None);
+}
+
+#[test]
+fn decimal_price() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            feeds: Some(
+                [(
+                    "orai".to_string(),
+                    FeedConfig {
+                        value_type: ValueType::Decimal256,
+                        ..FeedConfig::default()
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    // integers are valid decimals, but not the other way around
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: [("orai".to_string(), FeedValue("abc".to_string()))].into(),
+            latest: None,
+        },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [(
+            "orai".to_string(),
+            Decimal256::from_str("0.000000000001").unwrap().into(),
+        )]
+        .into(),
+    );
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.value_type, ValueType::Decimal256);
+    assert_eq!(price.price, FeedValue("0.000000000001".to_string()));
+}
+
+#[test]
+fn signed_value() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    // funding rate in basis points, between -10% and 10%
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            feeds: Some(
+                [(
+                    "orai".to_string(),
+                    FeedConfig {
+                        value_type: ValueType::Int128,
+                        min_value: Some(Int128::new(-1000).into()),
+                        max_value: Some(Int128::new(1000).into()),
+                        ..FeedConfig::default()
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    // out of bounds
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: [("orai".to_string(), Int128::new(-1500).into())].into(),
+            latest: None,
+        },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [("orai".to_string(), Int128::new(-5).into())].into(),
+    );
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.value_type, ValueType::Int128);
+    assert_eq!(price.price, FeedValue("-5".to_string()));
+
+    // signed median rounds down, whatever the signs
+    assert_eq!(
+        calculate_median_price(vec![Int128::new(-3), Int128::new(-4)]),
+        Some(Int128::new(-4))
+    );
+    assert_eq!(
+        calculate_median_price(vec![Int128::new(-1), Int128::new(2)]),
+        Some(Int128::new(0))
+    );
+    assert_eq!(
+        calculate_median_price(vec![Int128::new(-2), Int128::new(1)]),
+        Some(Int128::new(-1))
+    );
+    assert_eq!(
+        calculate_median_price(vec![Int128::new(i128::MIN), Int128::new(i128::MAX)]),
+        Some(Int128::new(-1))
+    );
+}
+
+#[test]
+fn plurality_value() {
+    // weight wins over the number of submissions
+    assert_eq!(
+        calculate_plurality(vec![
+            (FeedValue("halted".to_string()), 1),
+            (FeedValue("halted".to_string()), 1),
+            (FeedValue("open".to_string()), 3),
+        ]),
+        Some((FeedValue("open".to_string()), 3))
+    );
+    // on equal weight and count, the smallest value
+    assert_eq!(
+        calculate_plurality(vec![
+            (FeedValue("true".to_string()), 1),
+            (FeedValue("false".to_string()), 1),
+        ]),
+        Some((FeedValue("false".to_string()), 1))
+    );
+}
+
+#[test]
+fn bool_value() {
+    let (app, accounts, cw_oracle_hub_addr) = init_app();
+
+    let wasm = Wasm::new(&app);
+
+    let (member0, member1, member2) = (&accounts[0], &accounts[1], &accounts[2]);
+
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::UpdateConfig(ConfigUpdate {
+            price_keys: Some(vec!["orai".to_string(), "orai_halted".to_string()]),
+            feeds: Some(
+                [(
+                    "orai_halted".to_string(),
+                    FeedConfig {
+                        value_type: ValueType::Bool,
+                        ..FeedConfig::default()
+                    },
+                )]
+                .into(),
+            ),
+            ..ConfigUpdate::default()
+        }),
+        &[],
+        member0,
+    )
+    .unwrap();
+
+    // not a boolean
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: [
+                ("orai".to_string(), 11_000_000u128.into()),
+                ("orai_halted".to_string(), FeedValue("yes".to_string())),
+            ]
+            .into(),
+            latest: None,
+        },
+        &[],
+        member0,
+    )
+    .unwrap_err();
+
+    let proposal_id = feed_round(
+        &wasm,
+        &cw_oracle_hub_addr,
+        &[member0, member1, member2],
+        [
+            ("orai".to_string(), 11_000_000u128.into()),
+            ("orai_halted".to_string(), FeedValue("true".to_string())),
+        ]
+        .into(),
+    );
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai_halted".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.value_type, ValueType::Bool);
+    assert_eq!(price.price, FeedValue("true".to_string()));
+
+    // a split round passes, but no value has enough weight of its own to be dispatched
+    let split = |halted: &str| -> VoteData {
+        [
+            ("orai".to_string(), 12_000_000u128.into()),
+            ("orai_halted".to_string(), FeedValue(halted.to_string())),
+        ]
+        .into()
+    };
+    wasm.execute(
+        &cw_oracle_hub_addr,
+        &ExecuteMsg::Propose {
+            data: split("false"),
+            latest: None,
+        },
+        &[],
+        member0,
+    )
+    .unwrap();
+    for (member, halted) in [(member1, "false"), (member2, "true")] {
+        wasm.execute(
+            &cw_oracle_hub_addr,
+            &ExecuteMsg::Vote {
+                proposal_id: proposal_id + 1,
+                data: split(halted),
+            },
+            &[],
+            member,
+        )
+        .unwrap();
+    }
+
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai_halted".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.price, FeedValue("true".to_string()));
+    assert_eq!(price.proposal_id, proposal_id);
+    let price: PriceResponse = wasm
+        .query(
+            &cw_oracle_hub_addr,
+            &QueryMsg::Price {
+                key: "orai".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(price.proposal_id, proposal_id + 1);
 }
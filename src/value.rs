@@ -0,0 +1,308 @@
+use std::fmt;
+use std::str::FromStr;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::schemars::gen::SchemaGenerator;
+use cosmwasm_schema::schemars::schema::Schema;
+use cosmwasm_schema::schemars::JsonSchema;
+use cosmwasm_schema::serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use cosmwasm_std::{Binary, Decimal256, StdError, StdResult, Uint128, Uint256};
+
+/// How the values submitted for a key are parsed and aggregated
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum ValueType {
+    /// Integer with implicit decimals, dispatched with `append_price`
+    #[default]
+    Uint128,
+    /// Fixed point with 18 decimals, dispatched with `append_decimal_price`
+    Decimal256,
+    /// Signed integer for rates that can go negative, dispatched with `append_signed_value`
+    Int128,
+    /// The non-numeric types below are aggregated by weighted plurality and dispatched with `append_data`
+    String,
+    Bool,
+    /// Base64 encoded bytes, e.g. a block hash
+    Binary,
+}
+
+impl ValueType {
+    /// Whether the value parses as this type and lies within the optional bounds
+    pub fn validate(
+        &self,
+        value: &FeedValue,
+        min: Option<&FeedValue>,
+        max: Option<&FeedValue>,
+    ) -> bool {
+        match self {
+            ValueType::Uint128 => is_within::<Uint128>(value, min, max),
+            ValueType::Decimal256 => is_within::<Decimal256>(value, min, max),
+            ValueType::Int128 => is_within::<Int128>(value, min, max),
+            _ => self.to_data(value).is_ok(),
+        }
+    }
+
+    /// Typed form of a non-numeric value
+    pub fn to_data(&self, value: &FeedValue) -> StdResult<DataValue> {
+        match self {
+            ValueType::Bool => Ok(DataValue::Bool(value.parse()?)),
+            ValueType::Binary => Ok(DataValue::Binary(Binary::from_base64(&value.0)?)),
+            _ => Ok(DataValue::String(value.0.clone())),
+        }
+    }
+
+    /// Canonical form of a non-numeric value, so that equal submissions are tallied together
+    pub fn normalize(&self, value: &FeedValue) -> StdResult<FeedValue> {
+        Ok(match self.to_data(value)? {
+            DataValue::String(value) => FeedValue(value),
+            DataValue::Bool(value) => FeedValue(value.to_string()),
+            DataValue::Binary(value) => FeedValue(value.to_base64()),
+        })
+    }
+
+    /// Whether `value` moved at least `bps` basis points away from `last`
+    pub fn has_deviated(&self, last: &FeedValue, value: &FeedValue, bps: u64) -> bool {
+        match self {
+            ValueType::Uint128 => has_deviated::<Uint128>(last, value, bps),
+            ValueType::Decimal256 => has_deviated::<Decimal256>(last, value, bps),
+            ValueType::Int128 => has_deviated::<Int128>(last, value, bps),
+            // any change of a non-numeric value counts
+            _ => last != value,
+        }
+    }
+}
+
+/// A submitted or aggregated value, kept in the JSON string form of its ValueType
+#[cw_serde]
+#[derive(Default)]
+#[serde(transparent)]
+pub struct FeedValue(pub String);
+
+/// A non-numeric value as sent to the hook contracts
+#[cw_serde]
+pub enum DataValue {
+    String(String),
+    Bool(bool),
+    Binary(Binary),
+}
+
+impl FeedValue {
+    pub fn parse<T: FromStr>(&self) -> StdResult<T> {
+        self.0.parse().map_err(|_| {
+            StdError::parse_err(
+                std::any::type_name::<T>(),
+                format!("invalid value '{}'", self.0),
+            )
+        })
+    }
+}
+
+impl fmt::Display for FeedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u128> for FeedValue {
+    fn from(value: u128) -> Self {
+        Uint128::new(value).into()
+    }
+}
+
+impl From<Uint128> for FeedValue {
+    fn from(value: Uint128) -> Self {
+        FeedValue(value.to_string())
+    }
+}
+
+impl From<Decimal256> for FeedValue {
+    fn from(value: Decimal256) -> Self {
+        FeedValue(value.to_string())
+    }
+}
+
+impl From<Int128> for FeedValue {
+    fn from(value: Int128) -> Self {
+        FeedValue(value.to_string())
+    }
+}
+
+/// A signed 128 bit integer, serialized as a string like Uint128
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Int128(pub i128);
+
+impl Int128 {
+    pub const fn new(value: i128) -> Self {
+        Int128(value)
+    }
+
+    pub const fn i128(&self) -> i128 {
+        self.0
+    }
+}
+
+impl FromStr for Int128 {
+    type Err = StdError;
+
+    fn from_str(s: &str) -> StdResult<Self> {
+        s.parse::<i128>()
+            .map(Int128)
+            .map_err(|e| StdError::generic_err(format!("Parsing Int128: {}", e)))
+    }
+}
+
+impl fmt::Display for Int128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Int128 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Int128 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for Int128 {
+    fn schema_name() -> String {
+        "Int128".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// How much the submissions of a numeric key disagree in a round
+#[cw_serde]
+pub struct Dispersion {
+    pub min: FeedValue,
+    pub max: FeedValue,
+    /// Interquartile range, third minus first quartile
+    pub iqr: FeedValue,
+    /// Population standard deviation, in the same unit as the values
+    pub std_dev: Decimal256,
+}
+
+/// Numeric values aggregated by median
+pub trait Aggregate: FromStr + Ord + Clone + Into<FeedValue> {
+    /// Middle of two values, for the median of an even number of values
+    fn midpoint(a: &Self, b: &Self) -> Self;
+
+    /// Distance from `low` up to `high`
+    fn spread(low: &Self, high: &Self) -> Self;
+
+    /// Decimal form of a non-negative value
+    fn to_decimal(&self) -> Decimal256;
+
+    /// Whether `value` moved at least `bps` basis points away from `last`
+    fn deviates(last: &Self, value: &Self, bps: u64) -> bool;
+}
+
+impl Aggregate for Uint128 {
+    fn midpoint(a: &Self, b: &Self) -> Self {
+        // halve first so that the sum can not overflow
+        let (a, b) = (a.u128(), b.u128());
+        Uint128::new((a >> 1) + (b >> 1) + (a & b & 1))
+    }
+
+    fn spread(low: &Self, high: &Self) -> Self {
+        high.saturating_sub(*low)
+    }
+
+    fn to_decimal(&self) -> Decimal256 {
+        Decimal256::from_ratio(*self, 1u8)
+    }
+
+    fn deviates(last: &Self, value: &Self, bps: u64) -> bool {
+        let diff = if value > last {
+            *value - *last
+        } else {
+            *last - *value
+        };
+        diff.full_mul(10_000u64) >= last.full_mul(bps)
+    }
+}
+
+impl Aggregate for Decimal256 {
+    fn midpoint(a: &Self, b: &Self) -> Self {
+        let (a, b, two) = (a.atomics(), b.atomics(), Uint256::from(2u8));
+        Decimal256::new(a / two + b / two + (a % two + b % two) / two)
+    }
+
+    fn spread(low: &Self, high: &Self) -> Self {
+        if high > low {
+            *high - *low
+        } else {
+            Decimal256::zero()
+        }
+    }
+
+    fn to_decimal(&self) -> Decimal256 {
+        *self
+    }
+
+    fn deviates(last: &Self, value: &Self, bps: u64) -> bool {
+        let diff = if value > last {
+            *value - *last
+        } else {
+            *last - *value
+        };
+        diff >= *last * Decimal256::from_ratio(bps, 10_000u64)
+    }
+}
+
+impl Aggregate for Int128 {
+    fn midpoint(a: &Self, b: &Self) -> Self {
+        // halve first so that the sum can not overflow, rounding down like the unsigned types
+        Int128(a.0.div_euclid(2) + b.0.div_euclid(2) + (a.0.rem_euclid(2) + b.0.rem_euclid(2)) / 2)
+    }
+
+    fn spread(low: &Self, high: &Self) -> Self {
+        Int128(high.0.saturating_sub(low.0))
+    }
+
+    fn to_decimal(&self) -> Decimal256 {
+        Decimal256::from_ratio(self.0.unsigned_abs(), 1u8)
+    }
+
+    fn deviates(last: &Self, value: &Self, bps: u64) -> bool {
+        let diff = Uint128::new(value.0.abs_diff(last.0));
+        diff.full_mul(10_000u64) >= Uint128::new(last.0.unsigned_abs()).full_mul(bps)
+    }
+}
+
+fn is_within<T: Aggregate>(
+    value: &FeedValue,
+    min: Option<&FeedValue>,
+    max: Option<&FeedValue>,
+) -> bool {
+    let value = match value.parse::<T>() {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let above_min = match min {
+        Some(min) => min.parse::<T>().map(|min| value >= min).unwrap_or(false),
+        None => true,
+    };
+    let below_max = match max {
+        Some(max) => max.parse::<T>().map(|max| value <= max).unwrap_or(false),
+        None => true,
+    };
+    above_min && below_max
+}
+
+fn has_deviated<T: Aggregate>(last: &FeedValue, value: &FeedValue, bps: u64) -> bool {
+    match (last.parse::<T>(), value.parse::<T>()) {
+        // an unchanged value never deviates, even from zero
+        (Ok(last), Ok(value)) => last != value && T::deviates(&last, &value, bps),
+        _ => false,
+    }
+}